@@ -0,0 +1,33 @@
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    // CI can pass the hash in; otherwise ask git, if this is a checkout at all.
+    println!("cargo:rerun-if-env-changed=RUNNER_BUILD_HASH");
+    let hash = std::env::var("RUNNER_BUILD_HASH")
+        .ok()
+        .filter(|h| !h.is_empty())
+        .or_else(git_hash)
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=RUNNER_BUILD_HASH={}", hash);
+}
+
+fn git_hash() -> Option<String> {
+    let git_dir = git(&["rev-parse", "--git-dir"])?;
+    // Rebuild when HEAD moves, so the hash never goes stale.
+    for watched in ["HEAD", "refs", "packed-refs"] {
+        let path = Path::new(&git_dir).join(watched);
+        if path.exists() {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+    }
+    git(&["rev-parse", "--short=12", "HEAD"])
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_string())
+}
@@ -0,0 +1,373 @@
+use serde::Deserialize;
+use std::env;
+use std::fmt;
+use std::fs;
+
+/// Exit code for invalid command line or config file input.
+pub const EXIT_USAGE: i32 = 2;
+
+pub const USAGE: &str = "\
+Quest Passer runner - stands in for a game executable so Discord detects it.
+
+Usage: runner [OPTIONS]
+
+Options:
+  --title <TEXT>      Title shown in the window and tray tooltip [default: Quest Passer]
+  --icon <URL>        http(s) URL of an image to use as the window icon, or as the
+                      tray icon on Linux (not supported on macOS)
+  --tray              Start hidden in the system tray (not supported on macOS)
+  --duration <SECS>   Target play time shown by the progress display [default: 900]
+  --exit-after        Exit once the target duration has elapsed
+  --parent-pid <PID>  Exit as soon as the process with this PID is gone
+  --status            Write JSON-lines status events to stdout
+  --status-file <FILE>
+                      Write JSON-lines status events to FILE instead of stdout
+  --heartbeat <SECS>  Seconds between heartbeat status events [default: 5]
+  --control <PATH>    Accept control commands on a local socket at PATH
+  --config <FILE>     Read options from a JSON file; command line flags override it
+  -h, --help          Print this help and exit
+  -V, --version       Print the version and build hash and exit
+
+Config file keys mirror the long flags, e.g. {\"title\": \"My Game\", \"exit-after\": true}
+
+Control commands (one per line on --control, each answered with one JSON line):
+  status                  Report pid, title, elapsed and duration
+  stop                    Exit gracefully
+  set-title <TEXT>        Change the displayed title
+  extend-duration <SECS>  Add SECS to the target duration
+
+Exit codes:
+  0  Closed by the user
+  2  Invalid command line or config file
+  3  Target duration completed (--exit-after)
+  4  Parent process exited (--parent-pid)
+";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub title: String,
+    #[serde(rename = "tray")]
+    pub start_minimized: bool,
+    #[serde(rename = "icon")]
+    pub icon_url: Option<String>,
+    #[serde(rename = "duration")]
+    pub duration_secs: u64,
+    pub exit_after: bool,
+    pub parent_pid: Option<u32>,
+    #[serde(rename = "status")]
+    pub status_stdout: bool,
+    pub status_file: Option<String>,
+    #[serde(rename = "heartbeat")]
+    pub heartbeat_secs: u64,
+    #[serde(rename = "control")]
+    pub control_path: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            title: "Quest Passer".to_string(),
+            start_minimized: false,
+            icon_url: None,
+            duration_secs: 900,
+            exit_after: false,
+            parent_pid: None,
+            status_stdout: false,
+            status_file: None,
+            heartbeat_secs: 5,
+            control_path: None,
+        }
+    }
+}
+
+/// What the command line asked the runner to do.
+#[derive(Debug)]
+pub enum Action {
+    Run(Config),
+    Help,
+    Version,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    UnknownArgument(String),
+    MissingValue(&'static str),
+    InvalidValue {
+        option: &'static str,
+        value: String,
+        reason: &'static str,
+    },
+    ConfigFile {
+        path: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::UnknownArgument(arg) => write!(f, "unknown argument '{}'", arg),
+            ConfigError::MissingValue(option) => write!(f, "{} requires a value", option),
+            ConfigError::InvalidValue {
+                option,
+                value,
+                reason,
+            } => write!(f, "invalid value '{}' for {}: {}", value, option, reason),
+            ConfigError::ConfigFile { path, reason } => {
+                write!(f, "failed to read config file '{}': {}", path, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+pub fn parse_args() -> Result<Action, ConfigError> {
+    parse_from(env::args().skip(1)) // Skip program name
+}
+
+pub fn parse_from<I>(args: I) -> Result<Action, ConfigError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let args: Vec<String> = args.into_iter().collect();
+
+    // Help and version win over everything else, even otherwise invalid input.
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        return Ok(Action::Help);
+    }
+    if args.iter().any(|a| a == "-V" || a == "--version") {
+        return Ok(Action::Version);
+    }
+
+    // The config file is the base layer, so load it before applying any flags.
+    let mut config = match config_file_arg(&args)? {
+        Some(path) => load_file(path)?,
+        None => Config::default(),
+    };
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--title" => config.title = value(&mut args, "--title")?,
+            "--icon" => config.icon_url = Some(value(&mut args, "--icon")?),
+            "--tray" => config.start_minimized = true,
+            "--duration" => config.duration_secs = number(&mut args, "--duration")?,
+            "--exit-after" => config.exit_after = true,
+            "--parent-pid" => config.parent_pid = Some(number(&mut args, "--parent-pid")?),
+            "--status" => config.status_stdout = true,
+            "--status-file" => config.status_file = Some(value(&mut args, "--status-file")?),
+            "--heartbeat" => config.heartbeat_secs = number(&mut args, "--heartbeat")?,
+            "--control" => config.control_path = Some(value(&mut args, "--control")?),
+            "--config" => {
+                value(&mut args, "--config")?;
+            }
+            _ => return Err(ConfigError::UnknownArgument(arg)),
+        }
+    }
+
+    validate(&config)?;
+    Ok(Action::Run(config))
+}
+
+fn value(args: &mut impl Iterator<Item = String>, option: &'static str) -> Result<String, ConfigError> {
+    match args.next() {
+        Some(v) if !v.starts_with("--") => Ok(v),
+        _ => Err(ConfigError::MissingValue(option)),
+    }
+}
+
+fn number<T: std::str::FromStr>(
+    args: &mut impl Iterator<Item = String>,
+    option: &'static str,
+) -> Result<T, ConfigError> {
+    let raw = value(args, option)?;
+    raw.parse().map_err(|_| ConfigError::InvalidValue {
+        option,
+        value: raw,
+        reason: "expected a non-negative whole number",
+    })
+}
+
+fn config_file_arg(args: &[String]) -> Result<Option<&str>, ConfigError> {
+    match args.iter().position(|a| a == "--config") {
+        Some(i) => match args.get(i + 1) {
+            Some(path) if !path.starts_with("--") => Ok(Some(path)),
+            _ => Err(ConfigError::MissingValue("--config")),
+        },
+        None => Ok(None),
+    }
+}
+
+fn load_file(path: &str) -> Result<Config, ConfigError> {
+    let file_error = |reason: String| ConfigError::ConfigFile {
+        path: path.to_string(),
+        reason,
+    };
+    let contents = fs::read_to_string(path).map_err(|e| file_error(e.to_string()))?;
+    serde_json::from_str(&contents).map_err(|e| file_error(e.to_string()))
+}
+
+fn validate(config: &Config) -> Result<(), ConfigError> {
+    if config.title.trim().is_empty() {
+        return Err(ConfigError::InvalidValue {
+            option: "--title",
+            value: config.title.clone(),
+            reason: "title must not be empty",
+        });
+    }
+
+    if config.duration_secs == 0 {
+        return Err(ConfigError::InvalidValue {
+            option: "--duration",
+            value: "0".to_string(),
+            reason: "duration must be at least one second",
+        });
+    }
+
+    if config.heartbeat_secs == 0 {
+        return Err(ConfigError::InvalidValue {
+            option: "--heartbeat",
+            value: "0".to_string(),
+            reason: "heartbeat interval must be at least one second",
+        });
+    }
+
+    if let Some(url) = &config.icon_url {
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(ConfigError::InvalidValue {
+                option: "--icon",
+                value: url.clone(),
+                reason: "expected an http:// or https:// URL",
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn parse(args: &[&str]) -> Result<Action, ConfigError> {
+        parse_from(args.iter().map(|a| a.to_string()))
+    }
+
+    fn run(args: &[&str]) -> Config {
+        match parse(args) {
+            Ok(Action::Run(config)) => config,
+            other => panic!("expected a config for {:?}, got {:?}", args, other),
+        }
+    }
+
+    /// Writes `contents` to a config file unique to one test.
+    fn config_file(name: &str, contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("runner-config-{}-{}.json", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn defaults_without_arguments() {
+        let config = run(&[]);
+        assert_eq!(config.title, "Quest Passer");
+        assert_eq!(config.duration_secs, 900);
+        assert_eq!(config.heartbeat_secs, 5);
+        assert!(!config.exit_after && !config.status_stdout && !config.start_minimized);
+    }
+
+    #[test]
+    fn reads_every_flag() {
+        let config = run(&[
+            "--title", "Game", "--icon", "https://example.com/a.png", "--tray", "--duration", "60",
+            "--exit-after", "--parent-pid", "42", "--status", "--status-file", "out.jsonl",
+            "--heartbeat", "2", "--control", "/tmp/runner.sock",
+        ]);
+        assert_eq!(config.title, "Game");
+        assert_eq!(config.icon_url.as_deref(), Some("https://example.com/a.png"));
+        assert!(config.start_minimized && config.exit_after && config.status_stdout);
+        assert_eq!(config.duration_secs, 60);
+        assert_eq!(config.parent_pid, Some(42));
+        assert_eq!(config.status_file.as_deref(), Some("out.jsonl"));
+        assert_eq!(config.heartbeat_secs, 2);
+        assert_eq!(config.control_path.as_deref(), Some("/tmp/runner.sock"));
+    }
+
+    #[test]
+    fn rejects_unknown_arguments() {
+        assert!(matches!(parse(&["--titel", "x"]), Err(ConfigError::UnknownArgument(a)) if a == "--titel"));
+        assert!(matches!(parse(&["game.exe"]), Err(ConfigError::UnknownArgument(_))));
+    }
+
+    #[test]
+    fn rejects_missing_values() {
+        assert!(matches!(parse(&["--title"]), Err(ConfigError::MissingValue("--title"))));
+        assert!(matches!(parse(&["--icon", "--tray"]), Err(ConfigError::MissingValue("--icon"))));
+        assert!(matches!(parse(&["--config"]), Err(ConfigError::MissingValue("--config"))));
+    }
+
+    #[test]
+    fn rejects_invalid_numbers() {
+        for args in [["--duration", "-5"], ["--duration", "ten"], ["--parent-pid", "1.5"]] {
+            assert!(matches!(parse(&args), Err(ConfigError::InvalidValue { .. })), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn help_and_version_win() {
+        assert!(matches!(parse(&["--bogus", "-h"]), Ok(Action::Help)));
+        assert!(matches!(parse(&["--duration", "0", "--help"]), Ok(Action::Help)));
+        assert!(matches!(parse(&["--title", "-V"]), Ok(Action::Version)));
+        assert!(matches!(parse(&["--version", "--help"]), Ok(Action::Help)));
+    }
+
+    #[test]
+    fn flags_override_the_config_file() {
+        let path = config_file("override", r#"{"title": "From File", "duration": 120, "exit-after": true}"#);
+        let path = path.to_str().unwrap();
+        let config = run(&["--title", "From Flag", "--config", path]);
+        assert_eq!(config.title, "From Flag");
+        assert_eq!(config.duration_secs, 120);
+        assert!(config.exit_after);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn config_file_rejects_unknown_keys() {
+        let path = config_file("unknown", r#"{"titel": "Typo"}"#);
+        let result = parse(&["--config", path.to_str().unwrap()]);
+        assert!(matches!(result, Err(ConfigError::ConfigFile { reason, .. }) if reason.contains("titel")));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn config_file_must_exist() {
+        assert!(matches!(
+            parse(&["--config", "/nonexistent/runner.json"]),
+            Err(ConfigError::ConfigFile { .. })
+        ));
+    }
+
+    #[test]
+    fn validates_values() {
+        let invalid = |args: &[&str], expected: &str| match parse(args) {
+            Err(ConfigError::InvalidValue { option, .. }) => assert_eq!(option, expected, "{:?}", args),
+            other => panic!("expected {} to be rejected for {:?}, got {:?}", expected, args, other),
+        };
+        invalid(&["--title", "  "], "--title");
+        invalid(&["--duration", "0"], "--duration");
+        invalid(&["--heartbeat", "0"], "--heartbeat");
+        invalid(&["--icon", "file:///etc/passwd"], "--icon");
+
+        // Values from the config file are checked too
+        let path = config_file("validate", r#"{"duration": 0}"#);
+        assert!(matches!(
+            parse(&["--config", path.to_str().unwrap()]),
+            Err(ConfigError::InvalidValue { option: "--duration", .. })
+        ));
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+//! Local control socket (`--control <PATH>`).
+//!
+//! Each connection sends one command per line and gets one JSON line back.
+//! On Unix PATH is a Unix domain socket. Windows has no std support for those,
+//! so the runner listens on a loopback TCP port and writes `127.0.0.1:<port>`
+//! into PATH for the client to pick up.
+
+use crate::lifecycle;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process;
+use std::sync::OnceLock;
+use std::thread;
+
+static CONTROL_PATH: OnceLock<String> = OnceLock::new();
+
+/// Binds the control socket and serves it from a background thread.
+pub fn start(path: &str) -> io::Result<()> {
+    let listener = bind(path)?;
+    let _ = CONTROL_PATH.set(path.to_string());
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            thread::spawn(move || {
+                if let Ok(reader) = stream.try_clone() {
+                    serve(reader, stream);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+/// Removes the socket (or port file) so stale paths don't pile up.
+pub fn cleanup() {
+    if let Some(path) = CONTROL_PATH.get() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(unix)]
+fn bind(path: &str) -> io::Result<std::os::unix::net::UnixListener> {
+    // A previous runner that crashed may have left its socket behind.
+    let _ = fs::remove_file(path);
+    std::os::unix::net::UnixListener::bind(path)
+}
+
+#[cfg(windows)]
+fn bind(path: &str) -> io::Result<std::net::TcpListener> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    fs::write(path, listener.local_addr()?.to_string())?;
+    Ok(listener)
+}
+
+fn serve<R: Read, W: Write>(reader: R, mut writer: W) {
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        let (command, arg) = match line.trim().split_once(' ') {
+            Some((command, arg)) => (command, arg.trim()),
+            None => (line.trim(), ""),
+        };
+        if command.is_empty() {
+            continue;
+        }
+
+        let stop = command == "stop";
+        let reply = dispatch(command, arg);
+        if writeln!(writer, "{}", reply).and_then(|_| writer.flush()).is_err() {
+            return;
+        }
+        if stop {
+            lifecycle::exit("stopped", lifecycle::EXIT_CLOSED);
+        }
+    }
+}
+
+fn dispatch(command: &str, arg: &str) -> Value {
+    match command {
+        "status" => json!({
+            "ok": true,
+            "pid": process::id(),
+            "title": lifecycle::title(),
+            "elapsed": lifecycle::elapsed().as_secs(),
+            "duration": lifecycle::duration().as_secs(),
+        }),
+        "stop" => json!({ "ok": true }),
+        "set-title" => {
+            if arg.is_empty() {
+                return error("set-title requires a title");
+            }
+            lifecycle::set_title(arg.to_string());
+            json!({ "ok": true })
+        }
+        "extend-duration" => match arg.parse::<u64>() {
+            Ok(secs) => {
+                lifecycle::extend_duration(secs);
+                json!({ "ok": true, "duration": lifecycle::duration().as_secs() })
+            }
+            Err(_) => error("extend-duration requires a whole number of seconds"),
+        },
+        _ => error(&format!("unknown command '{}'", command)),
+    }
+}
+
+fn error(message: &str) -> Value {
+    json!({ "ok": false, "error": message })
+}
@@ -0,0 +1,169 @@
+use crate::config::Config;
+use crate::control;
+use crate::status::{self, Event};
+use std::process;
+#[cfg(unix)]
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Exit code when the runner is closed normally (window or tray "Quit").
+pub const EXIT_CLOSED: i32 = 0;
+/// Exit code when `--exit-after` ends the runner at the target duration.
+pub const EXIT_COMPLETED: i32 = 3;
+/// Exit code when the `--parent-pid` process is no longer running.
+pub const EXIT_PARENT_GONE: i32 = 4;
+/// Exit code after a SIGTERM/SIGINT, following the shell's 128 + signal convention.
+#[cfg(unix)]
+pub const EXIT_TERMINATED: i32 = 128 + libc::SIGTERM;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Lifecycle {
+    started: Instant,
+    duration_secs: AtomicU64,
+    title: Mutex<String>,
+}
+
+static LIFECYCLE: OnceLock<Lifecycle> = OnceLock::new();
+#[cfg(unix)]
+static TERMINATE_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Starts the session clock and the watchdog thread that reports progress
+/// and ends the process when the target duration or parent is gone.
+pub fn start(config: &Config) {
+    let _ = LIFECYCLE.set(Lifecycle {
+        started: Instant::now(),
+        duration_secs: AtomicU64::new(config.duration_secs),
+        title: Mutex::new(config.title.clone()),
+    });
+
+    #[cfg(unix)]
+    install_signal_handlers();
+
+    status::emit(Event::Started {
+        pid: process::id(),
+        title: &config.title,
+        duration: config.duration_secs,
+    });
+
+    let exit_after = config.exit_after;
+    let parent_pid = config.parent_pid;
+    let heartbeat_secs = config.heartbeat_secs;
+    thread::spawn(move || {
+        let mut completed = false;
+        let mut last_heartbeat = 0;
+        loop {
+            let elapsed = elapsed().as_secs();
+            let target = duration().as_secs();
+
+            // Extending the duration re-arms completion.
+            if completed && elapsed < target {
+                completed = false;
+            }
+            if !completed && elapsed >= target {
+                completed = true;
+                status::emit(Event::Completed { elapsed });
+                if exit_after {
+                    exit("completed", EXIT_COMPLETED);
+                }
+            }
+            if elapsed >= last_heartbeat + heartbeat_secs {
+                last_heartbeat = elapsed;
+                status::emit(Event::Heartbeat {
+                    elapsed,
+                    duration: target,
+                });
+            }
+            if let Some(pid) = parent_pid {
+                if !process_alive(pid) {
+                    exit("parent_gone", EXIT_PARENT_GONE);
+                }
+            }
+            #[cfg(unix)]
+            if TERMINATE_REQUESTED.load(Ordering::Relaxed) {
+                exit("terminated", EXIT_TERMINATED);
+            }
+
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+/// Reports why the runner is going away, then exits with `code`.
+pub fn exit(reason: &str, code: i32) -> ! {
+    status::emit(Event::Exiting { reason, code });
+    control::cleanup();
+    process::exit(code);
+}
+
+fn lifecycle() -> &'static Lifecycle {
+    LIFECYCLE.get().expect("lifecycle::start must be called first")
+}
+
+/// Time since the runner started.
+pub fn elapsed() -> Duration {
+    lifecycle().started.elapsed()
+}
+
+/// Target play time the progress display counts towards.
+pub fn duration() -> Duration {
+    Duration::from_secs(lifecycle().duration_secs.load(Ordering::Relaxed))
+}
+
+/// Lengthens the target duration, e.g. from a control `extend-duration` command.
+pub fn extend_duration(secs: u64) {
+    lifecycle().duration_secs.fetch_add(secs, Ordering::Relaxed);
+}
+
+/// Title currently shown by the OS backend.
+pub fn title() -> String {
+    lifecycle().title.lock().map(|t| t.clone()).unwrap_or_default()
+}
+
+pub fn set_title(title: String) {
+    if let Ok(mut current) = lifecycle().title.lock() {
+        *current = title;
+    }
+}
+
+#[cfg(unix)]
+fn install_signal_handlers() {
+    // Only flag the request here; the watchdog does the actual (non signal-safe) exit.
+    extern "C" fn on_signal(_: libc::c_int) {
+        TERMINATE_REQUESTED.store(true, Ordering::Relaxed);
+    }
+
+    let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGTERM, handler);
+        libc::signal(libc::SIGINT, handler);
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    // Signal 0 only checks whether the process exists; EPERM still means it does.
+    let ret = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    ret == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+fn process_alive(pid: u32) -> bool {
+    use windows::Win32::Foundation::{CloseHandle, ERROR_ACCESS_DENIED, WAIT_TIMEOUT};
+    use windows::Win32::System::Threading::{OpenProcess, WaitForSingleObject, PROCESS_SYNCHRONIZE};
+
+    unsafe {
+        match OpenProcess(PROCESS_SYNCHRONIZE, false, pid) {
+            Ok(handle) => {
+                let alive = WaitForSingleObject(handle, 0) == WAIT_TIMEOUT;
+                let _ = CloseHandle(handle);
+                alive
+            }
+            // Like EPERM on Unix: it exists, we just may not wait on it
+            Err(e) => e.code() == ERROR_ACCESS_DENIED.to_hresult(),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+#![cfg(target_os = "linux")]
+
+use crate::config::Config;
+use std::thread;
+use std::time::Duration;
+
+pub fn run(config: Config) {
+    eprintln!("Starting Linux Runner: {}", config.title);
+
+    // There is no window on Linux; `--tray` is the only visible surface.
+    if config.icon_url.is_some() && !(cfg!(feature = "tray") && config.start_minimized) {
+        eprintln!("--icon is only shown in the tray on Linux (--tray, built with `--features tray`), ignoring it");
+    }
+    if config.start_minimized {
+        run_tray(&config);
+        return;
+    }
+
+    // Headless: Discord only needs the process to exist under the right name.
+    loop {
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+#[cfg(feature = "tray")]
+fn run_tray(config: &Config) {
+    use crate::lifecycle;
+    use crate::status::{self, Event};
+    use crate::tray::create_tray_icon;
+    use tray_icon::menu::{Menu, MenuEvent, MenuItem};
+
+    if let Err(e) = gtk::init() {
+        eprintln!("Failed to initialize GTK, running headless: {}", e);
+        loop {
+            thread::sleep(Duration::from_secs(1));
+        }
+    }
+
+    let tray_menu = Menu::new();
+    let quit_i = MenuItem::new("Quit", true, None);
+    let _ = tray_menu.append(&quit_i);
+
+    // The tray icon lives in the closure for as long as the GTK main loop runs.
+    let tray = create_tray_icon(tray_menu, &config.title);
+    if let Some(url) = &config.icon_url {
+        match load_icon(url) {
+            Ok(icon) => match tray.set_icon(Some(icon)) {
+                Ok(()) => status::emit(Event::IconLoaded { url }),
+                Err(e) => status::emit(Event::IconFailed { url, error: &e.to_string() }),
+            },
+            Err(e) => {
+                eprintln!("Failed to load icon {}: {}", url, e);
+                status::emit(Event::IconFailed { url, error: &e });
+            }
+        }
+    }
+    let mut displayed_title = config.title.clone();
+
+    // Menu events arrive on a channel, so poll it from the GTK main loop.
+    gtk::glib::timeout_add_local(Duration::from_millis(100), move || {
+        let title = lifecycle::title();
+        if title != displayed_title {
+            let _ = tray.set_tooltip(Some(&title));
+            displayed_title = title;
+        }
+        if let Ok(event) = MenuEvent::receiver().try_recv() {
+            if event.id == quit_i.id() {
+                gtk::main_quit();
+                return gtk::glib::ControlFlow::Break;
+            }
+        }
+        gtk::glib::ControlFlow::Continue
+    });
+
+    gtk::main();
+}
+
+/// Downloads and decodes the `--icon` image.
+#[cfg(feature = "tray")]
+fn load_icon(url: &str) -> Result<tray_icon::Icon, String> {
+    use std::io::Read;
+
+    let resp = ureq::get(url)
+        .set("User-Agent", "QuestPasser/1.0")
+        .timeout(Duration::from_secs(15))
+        .call()
+        .map_err(|e| e.to_string())?;
+    let mut bytes = Vec::new();
+    resp.into_reader().read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+    let image = image::load_from_memory(&bytes).map_err(|e| e.to_string())?.to_rgba8();
+    let (width, height) = image.dimensions();
+    tray_icon::Icon::from_rgba(image.into_raw(), width, height).map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "tray"))]
+fn run_tray(_config: &Config) {
+    eprintln!("Tray support was not compiled in (build with `--features tray`), running headless");
+    loop {
+        thread::sleep(Duration::from_secs(1));
+    }
+}
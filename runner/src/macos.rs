@@ -1,55 +1,20 @@
 
 #![cfg(target_os = "macos")]
 
-use std::env;
+use crate::config::Config;
 use std::thread;
 use std::time::Duration;
 
-#[derive(Debug)]
-pub struct Config {
-    pub title: String,
-    pub start_minimized: bool,
-    pub icon_url: Option<String>,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Config {
-            title: "Quest Passer".to_string(),
-            start_minimized: false,
-            icon_url: None,
-        }
+pub fn run(config: Config) {
+    eprintln!("Starting MacOS Runner Stub...");
+    eprintln!("Title: {}", config.title);
+    // Nothing here runs an AppKit event loop, which both would need.
+    if config.start_minimized {
+        eprintln!("--tray is not supported on macOS yet, running without a tray icon");
     }
-}
-
-pub fn parse_args() -> Config {
-    // Basic argument parsing, similar to Windows but potentially simpler for now
-    let args: Vec<String> = env::args().collect();
-    let mut config = Config::default();
-
-    let mut i = 1;
-    while i < args.len() {
-        match args[i].as_str() {
-            "--title" => {
-                if i + 1 < args.len() {
-                    config.title = args[i + 1].clone();
-                    i += 2;
-                } else {
-                    i += 1;
-                }
-            }
-            _ => {
-                i += 1;
-            }
-        }
+    if config.icon_url.is_some() {
+        eprintln!("--icon is not supported on macOS yet, ignoring it");
     }
-    config
-}
-
-pub fn run() {
-    println!("Starting MacOS Runner Stub...");
-    let config = parse_args();
-    println!("Title: {}", config.title);
 
     // On Mac, we might just sleep to simulate the process running.
     // Creating a window without a full framework (like Cocoa) in raw Rust is complex.
@@ -1,4 +1,8 @@
-
+mod config;
+mod control;
+mod lifecycle;
+mod status;
+#[cfg(any(not(target_os = "linux"), feature = "tray"))]
 mod tray;
 
 #[cfg(target_os = "windows")]
@@ -11,6 +15,37 @@ mod macos;
 #[cfg(target_os = "macos")]
 use macos as os;
 
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+use linux as os;
+
+use config::Action;
+use std::process;
+
 fn main() {
-    os::run();
+    match config::parse_args() {
+        Ok(Action::Run(config)) => {
+            if let Err(e) = status::init(&config) {
+                eprintln!("runner: failed to open status output: {}", e);
+                process::exit(config::EXIT_USAGE);
+            }
+            lifecycle::start(&config);
+            if let Some(path) = &config.control_path {
+                if let Err(e) = control::start(path) {
+                    eprintln!("runner: failed to open control socket '{}': {}", path, e);
+                    lifecycle::exit("control_failed", config::EXIT_USAGE);
+                }
+            }
+            os::run(config);
+            lifecycle::exit("closed", lifecycle::EXIT_CLOSED);
+        }
+        Ok(Action::Help) => print!("{}", config::USAGE),
+        Ok(Action::Version) => println!("runner {} ({})", env!("CARGO_PKG_VERSION"), env!("RUNNER_BUILD_HASH")),
+        Err(e) => {
+            eprintln!("runner: {}", e);
+            eprintln!("Run 'runner --help' for usage.");
+            process::exit(config::EXIT_USAGE);
+        }
+    }
 }
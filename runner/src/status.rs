@@ -0,0 +1,78 @@
+use crate::config::Config;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One line of the machine-readable status stream (`--status` / `--status-file`).
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    Started {
+        pid: u32,
+        title: &'a str,
+        duration: u64,
+    },
+    // Only the Windows window and the Linux tray load `--icon`.
+    #[cfg_attr(not(any(windows, all(target_os = "linux", feature = "tray"))), allow(dead_code))]
+    IconLoaded {
+        url: &'a str,
+    },
+    #[cfg_attr(not(any(windows, all(target_os = "linux", feature = "tray"))), allow(dead_code))]
+    IconFailed {
+        url: &'a str,
+        error: &'a str,
+    },
+    Heartbeat {
+        elapsed: u64,
+        duration: u64,
+    },
+    Completed {
+        elapsed: u64,
+    },
+    Exiting {
+        reason: &'a str,
+        code: i32,
+    },
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    ts: u128,
+    #[serde(flatten)]
+    event: &'a Event<'a>,
+}
+
+static SINK: OnceLock<Mutex<Box<dyn Write + Send>>> = OnceLock::new();
+
+/// Opens the status sink requested by the config. Without `--status` or
+/// `--status-file` every `emit` is a no-op.
+pub fn init(config: &Config) -> io::Result<()> {
+    let sink: Box<dyn Write + Send> = match (&config.status_file, config.status_stdout) {
+        (Some(path), _) => Box::new(File::create(path)?),
+        (None, true) => Box::new(io::stdout()),
+        (None, false) => return Ok(()),
+    };
+    let _ = SINK.set(Mutex::new(sink));
+    Ok(())
+}
+
+pub fn emit(event: Event) {
+    let Some(sink) = SINK.get() else {
+        return;
+    };
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let Ok(json) = serde_json::to_string(&Line { ts, event: &event }) else {
+        return;
+    };
+
+    // A reader that went away must never take the runner down with it.
+    if let Ok(mut sink) = sink.lock() {
+        let _ = writeln!(sink, "{}", json);
+        let _ = sink.flush();
+    }
+}
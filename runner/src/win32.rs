@@ -4,7 +4,6 @@
 use std::env;
 pub use std::ffi::OsStr; // pub use for re-export if needed, or just use
 use std::os::windows::ffi::OsStrExt;
-use std::time::Instant;
 use windows::core::PCWSTR;
 use windows::Win32::Foundation::{COLORREF, HINSTANCE, HWND, LPARAM, LRESULT, RECT, SIZE, WPARAM};
 use windows::Win32::Graphics::Gdi::{
@@ -32,6 +31,9 @@ use std::ffi::c_void;
 // SS_CENTER is 1. Defining it locally as WINDOW_STYLE(1)
 const SS_CENTER: WINDOW_STYLE = WINDOW_STYLE(1);
 
+use crate::config::Config;
+use crate::lifecycle;
+use crate::status::{self, Event};
 use crate::tray;
 use tray::create_tray_icon;
 
@@ -42,61 +44,12 @@ const BACKGROUND_COLOR: u32 = 0x001E1E2E; // Dark blue-gray
 const TEXT_COLOR: u32 = 0x00CDD6F4; // Light gray/white text
 
 static mut TITLE_LABEL: Option<HWND> = None;
-static mut START_TIME: Option<Instant> = None;
 static mut DURATION_LABEL: Option<HWND> = None;
 static mut PROGRESS_BAR_HWND: Option<HWND> = None;
 
-#[derive(Debug)]
-pub struct Config {
-    pub title: String,
-    pub start_minimized: bool,
-    pub icon_url: Option<String>,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Config {
-            title: "Quest Passer".to_string(),
-            start_minimized: false,
-            icon_url: None,
-        }
-    }
-}
-
-pub fn parse_args() -> Config {
-    let args: Vec<String> = env::args().collect();
-    let mut config = Config::default();
-
-    let mut i = 1; // Skip program name
-    while i < args.len() {
-        match args[i].as_str() {
-            "--title" => {
-                if i + 1 < args.len() {
-                    config.title = args[i + 1].clone();
-                    i += 2;
-                } else {
-                    i += 1;
-                }
-            }
-            "--icon" => {
-                if i + 1 < args.len() {
-                    config.icon_url = Some(args[i + 1].clone());
-                    i += 2;
-                } else {
-                    i += 1;
-                }
-            }
-            "--tray" => {
-                config.start_minimized = true;
-                i += 1;
-            }
-            _ => {
-                i += 1;
-            }
-        }
-    }
-
-    config
+thread_local! {
+    // Title last pushed to the window; only touched from the UI thread
+    static DISPLAYED_TITLE: std::cell::RefCell<String> = const { std::cell::RefCell::new(String::new()) };
 }
 
 // Helper to convert Rust string to wide string (UTF-16) for Windows APIs
@@ -224,29 +177,46 @@ unsafe extern "system" fn window_proc(
             LRESULT(GetStockObject(NULL_BRUSH).0 as isize)
         }
         WM_TIMER => {
-            if let Some(start) = START_TIME {
-                let elapsed = start.elapsed();
-                let secs = elapsed.as_secs();
-                let hours = secs / 3600;
-                let minutes = (secs % 3600) / 60;
-                let seconds = secs % 60;
-                let time_str = format!("Running: {:02}:{:02}:{:02}", hours, minutes, seconds);
-
-                if let Some(label_hwnd) = DURATION_LABEL {
-                    let wide_text = to_wstring(&time_str);
-                    let _ = SetWindowTextW(label_hwnd, PCWSTR(wide_text.as_ptr()));
+            let secs = lifecycle::elapsed().as_secs();
+            let target = lifecycle::duration().as_secs();
+
+            // Pick up title changes from the control socket
+            let title = lifecycle::title();
+            let title_changed = DISPLAYED_TITLE.with(|shown| *shown.borrow() != title);
+            if title_changed {
+                let wide_title = to_wstring(&title);
+                let _ = SetWindowTextW(hwnd, PCWSTR(wide_title.as_ptr()));
+                if let Some(label_hwnd) = TITLE_LABEL {
+                    let _ = SetWindowTextW(label_hwnd, PCWSTR(wide_title.as_ptr()));
                 }
+                DISPLAYED_TITLE.with(|shown| *shown.borrow_mut() = title);
+            }
+            let hours = secs / 3600;
+            let minutes = (secs % 3600) / 60;
+            let seconds = secs % 60;
+            let time_str = format!("Running: {:02}:{:02}:{:02}", hours, minutes, seconds);
+
+            if let Some(label_hwnd) = DURATION_LABEL {
+                let wide_text = to_wstring(&time_str);
+                let _ = SetWindowTextW(label_hwnd, PCWSTR(wide_text.as_ptr()));
+            }
 
-                if let Some(pb_hwnd) = PROGRESS_BAR_HWND {
-                    // Cap at 900
-                    let progress = if secs > 900 { 900 } else { secs };
-                    let _ = windows::Win32::UI::WindowsAndMessaging::SendMessageW(
-                        pb_hwnd,
-                        PBM_SETPOS,
-                        Some(WPARAM(progress as usize)),
-                        Some(LPARAM(0)),
-                    );
-                }
+            if let Some(pb_hwnd) = PROGRESS_BAR_HWND {
+                // The target can be extended over the control socket
+                let _ = windows::Win32::UI::WindowsAndMessaging::SendMessageW(
+                    pb_hwnd,
+                    PBM_SETRANGE32,
+                    Some(WPARAM(0)),
+                    Some(LPARAM(target as isize)),
+                );
+                // Cap at the target duration
+                let progress = secs.min(target);
+                let _ = windows::Win32::UI::WindowsAndMessaging::SendMessageW(
+                    pb_hwnd,
+                    PBM_SETPOS,
+                    Some(WPARAM(progress as usize)),
+                    Some(LPARAM(0)),
+                );
             }
             LRESULT(0)
         }
@@ -296,15 +266,10 @@ fn create_native_window(title: &str) -> Result<(HWND, HINSTANCE), Box<dyn std::e
     }
 }
 
+/// Debug output goes to stderr, never to a file: the working directory is
+/// the game folder, which must hold nothing but the runner.
 fn log_debug(msg: &str) {
-    use std::fs::OpenOptions;
-    use std::io::Write;
-    if let Ok(mut file) = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("runner_debug.txt") {
-        let _ = writeln!(file, "{}", msg);
-    }
+    eprintln!("{}", msg);
 }
 
 fn load_icon_from_url(url: &str) -> Option<HICON> {
@@ -317,6 +282,7 @@ fn load_icon_from_url(url: &str) -> Option<HICON> {
         
     if let Err(e) = &resp {
         log_debug(&format!("Download failed: {}", e));
+        status::emit(Event::IconFailed { url, error: &e.to_string() });
         return None;
     }
     
@@ -324,6 +290,7 @@ fn load_icon_from_url(url: &str) -> Option<HICON> {
     let mut bytes = Vec::new();
     if let Err(e) = resp.into_reader().read_to_end(&mut bytes) {
         log_debug(&format!("Read failed: {}", e));
+        status::emit(Event::IconFailed { url, error: &e.to_string() });
         return None;
     }
     log_debug(&format!("Downloaded {} bytes", bytes.len()));
@@ -332,6 +299,7 @@ fn load_icon_from_url(url: &str) -> Option<HICON> {
     let img_result = image::load_from_memory(&bytes);
     if let Err(e) = &img_result {
         log_debug(&format!("Decode failed: {}", e));
+        status::emit(Event::IconFailed { url, error: &e.to_string() });
         return None;
     }
     
@@ -365,23 +333,22 @@ fn load_icon_from_url(url: &str) -> Option<HICON> {
         
         if hicon.is_some() {
             log_debug("CreateIcon success");
+            status::emit(Event::IconLoaded { url });
         } else {
             log_debug("CreateIcon failed");
+            status::emit(Event::IconFailed { url, error: "CreateIcon failed" });
         }
         hicon
     }
 }
 
-pub fn run() {
+pub fn run(config: Config) {
     log_debug("Runner started!");
     let args: Vec<String> = env::args().collect();
     for (i, arg) in args.iter().enumerate() {
         log_debug(&format!("Arg {}: {}", i, arg));
     }
 
-    let config = parse_args();
-    log_debug("Args parsed");
-
     let tray_menu = tray_icon::menu::Menu::new();
     let quit_i = tray_icon::menu::MenuItem::new("Quit", true, None);
     let show_i = tray_icon::menu::MenuItem::new("Show", true, None);
@@ -505,12 +472,12 @@ pub fn run() {
         );
         
         if let Ok(pb) = pb {
-            // Set Range 0 to 900 (15 minutes in seconds)
+            // Set Range 0 to the target duration in seconds (--duration)
             let _ = windows::Win32::UI::WindowsAndMessaging::SendMessageW(
                 pb,
                 PBM_SETRANGE32,
                 Some(WPARAM(0)),
-                Some(LPARAM(900)),
+                Some(LPARAM(lifecycle::duration().as_secs() as isize)),
             );
             Some(pb)
         } else {
@@ -523,7 +490,6 @@ pub fn run() {
         TITLE_LABEL = title_label_hwnd;
         DURATION_LABEL = duration_label_hwnd;
         PROGRESS_BAR_HWND = progress_bar_hwnd;
-        START_TIME = Some(Instant::now());
         // SetTimer expecting: HWND, nIDEvent, uElapse, lpTimerFunc
         let timer: TIMERPROC = None;
         SetTimer(Some(hwnd), 1, 1000, timer);
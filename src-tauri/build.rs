@@ -1,3 +1,21 @@
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+
 fn main() {
+  // One runner per target, e.g. resources/runner-x86_64-unknown-linux-gnu
+  let target = env::var("TARGET").unwrap_or_default();
+  let extension = if target.contains("windows") { ".exe" } else { "" };
+  let resource = format!("resources/runner-{}{}", target, extension);
+  println!("cargo:rustc-env=RUNNER_TARGET={}", target);
+  println!("cargo:rustc-env=RUNNER_RESOURCE={}", resource);
+
+  // The app checks the bundled runner against this before copying it anywhere
+  println!("cargo:rerun-if-changed={}", resource);
+  let sha256: String = match fs::read(&resource) {
+    Ok(data) => Sha256::digest(&data).iter().map(|b| format!("{:02x}", b)).collect(),
+    Err(_) => String::new(),
+  };
+  println!("cargo:rustc-env=RUNNER_SHA256={}", sha256);
   tauri_build::build()
 }
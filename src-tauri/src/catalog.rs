@@ -0,0 +1,621 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+
+use crate::catalog_cache::Freshness;
+use crate::diagnostics::{FetchReport, SourceStatus};
+use crate::search::{SearchIndex, SearchPage};
+
+/// How a game is recognised by Discord: the executable it looks for on each OS.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Executable {
+    /// `win32`, `darwin` or `linux`.
+    pub os: String,
+    /// File name, possibly with a leading relative path such as `win64/game.exe`.
+    pub name: String,
+    /// A launcher rather than the game itself; Discord does not count it as playing.
+    #[serde(default)]
+    pub is_launcher: bool,
+    /// Command line Discord expects the process to have been started with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<String>,
+}
+
+/// A detectable application, normalised from whichever source listed it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectableApp {
+    /// Discord application id (a snowflake).
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Icon hash for `cdn.discordapp.com/app-icons/<id>/<hash>.png`.
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub splash: Option<String>,
+    #[serde(default)]
+    pub executables: Vec<Executable>,
+}
+
+/// What `fetch_game_list` returns. The games themselves stay in the backend
+/// and are read with `get_catalog_page`, `get_game` and `search_games`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Catalog {
+    pub total: usize,
+    /// The worst freshness among the sources that answered.
+    pub freshness: Freshness,
+    /// Unix time in milliseconds of the oldest data in `games`.
+    pub updated_at: Option<u64>,
+}
+
+/// The schema a source's entries follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceFormat {
+    /// `discord.com/api/applications/detectable`.
+    Discord,
+    /// The community gist of extra detectable games.
+    Gist,
+    /// `DetectableApp` as returned by `fetch_game_list`, for curated lists.
+    Native,
+}
+
+/// An id that may be sent as a JSON string or number.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawId {
+    Text(String),
+    Number(u64),
+}
+
+impl RawId {
+    fn into_string(self) -> String {
+        match self {
+            RawId::Text(id) => id.trim().to_string(),
+            RawId::Number(id) => id.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RawExecutable {
+    #[serde(default)]
+    os: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    is_launcher: bool,
+    #[serde(default)]
+    arguments: Option<String>,
+}
+
+/// Entry of Discord's detectable list. The icon is only published as
+/// `icon_hash`, and `aliases` and `executables` are left out rather than
+/// empty when there are none.
+#[derive(Deserialize)]
+struct DiscordApp {
+    id: RawId,
+    name: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    icon_hash: Option<String>,
+    #[serde(default)]
+    splash: Option<String>,
+    #[serde(default)]
+    executables: Vec<RawExecutable>,
+}
+
+/// Entry of the community gist. It follows an older dump of the Discord list:
+/// the icon is under `icon` (some entries still use `icon_hash`), ids are
+/// sometimes plain numbers and executables may come as `null`.
+#[derive(Deserialize)]
+struct GistApp {
+    id: RawId,
+    name: String,
+    #[serde(default)]
+    aliases: Option<Vec<String>>,
+    #[serde(default)]
+    icon: Option<String>,
+    #[serde(default)]
+    icon_hash: Option<String>,
+    #[serde(default)]
+    splash: Option<String>,
+    #[serde(default)]
+    executables: Option<Vec<RawExecutable>>,
+}
+
+/// An entry a source listed that could not be used.
+#[derive(Debug, Clone, Serialize)]
+pub struct MalformedEntry {
+    /// Position in the source's list.
+    pub index: usize,
+    pub id: Option<String>,
+    pub error: String,
+}
+
+/// Turns one raw entry in `format` into a `DetectableApp`. Entries without
+/// an id or a name are rejected.
+pub fn normalize(format: SourceFormat, entry: Value) -> Result<DetectableApp, String> {
+    let app = match format {
+        SourceFormat::Discord => {
+            let raw: DiscordApp = serde_json::from_value(entry).map_err(|e| e.to_string())?;
+            DetectableApp {
+                id: raw.id.into_string(),
+                name: raw.name,
+                aliases: raw.aliases,
+                icon: raw.icon_hash,
+                splash: raw.splash,
+                executables: normalize_executables(raw.executables),
+            }
+        }
+        SourceFormat::Gist => {
+            let raw: GistApp = serde_json::from_value(entry).map_err(|e| e.to_string())?;
+            DetectableApp {
+                id: raw.id.into_string(),
+                name: raw.name,
+                aliases: raw.aliases.unwrap_or_default(),
+                icon: raw.icon.or(raw.icon_hash),
+                splash: raw.splash,
+                executables: normalize_executables(raw.executables.unwrap_or_default()),
+            }
+        }
+        SourceFormat::Native => serde_json::from_value(entry).map_err(|e| e.to_string())?,
+    };
+    clean(app)
+}
+
+/// Rules shared by every source: trimmed text, empty strings become `None`
+/// and aliases repeating the name are dropped.
+fn clean(mut app: DetectableApp) -> Result<DetectableApp, String> {
+    app.name = app.name.trim().to_string();
+    if app.id.is_empty() {
+        return Err("empty id".to_string());
+    }
+    if app.name.is_empty() {
+        return Err("empty name".to_string());
+    }
+    app.icon = non_empty(app.icon);
+    app.splash = non_empty(app.splash);
+
+    let mut seen = HashSet::new();
+    seen.insert(app.name.to_lowercase());
+    app.aliases = app
+        .aliases
+        .into_iter()
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty() && seen.insert(a.to_lowercase()))
+        .collect();
+    Ok(app)
+}
+
+fn normalize_executables(raw: Vec<RawExecutable>) -> Vec<Executable> {
+    raw.into_iter()
+        .filter_map(|e| {
+            let name = e.name.trim().to_string();
+            if name.is_empty() {
+                return None;
+            }
+            Some(Executable {
+                os: e.os.trim().to_lowercase(),
+                name,
+                is_launcher: e.is_launcher,
+                arguments: non_empty(e.arguments),
+            })
+        })
+        .collect()
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Normalises a whole list in `format`. Entries that don't parse are set
+/// aside rather than failing the list, so one malformed record cannot empty
+/// the catalog.
+pub fn normalize_list(format: SourceFormat, entries: Vec<Value>) -> (Vec<DetectableApp>, Vec<MalformedEntry>) {
+    let mut apps = Vec::with_capacity(entries.len());
+    let mut malformed = Vec::new();
+    for (index, entry) in entries.into_iter().enumerate() {
+        let id = match entry.get("id") {
+            Some(Value::String(id)) => Some(id.clone()),
+            Some(Value::Number(id)) => Some(id.to_string()),
+            _ => None,
+        };
+        match normalize(format, entry) {
+            Ok(app) => apps.push(app),
+            Err(error) => malformed.push(MalformedEntry { index, id, error }),
+        }
+    }
+    (apps, malformed)
+}
+
+/// Whether at least one of `entries` normalises in `format`, i.e. whether a
+/// list is worth keeping as a source's last good copy.
+pub fn any_usable(format: SourceFormat, entries: &[Value]) -> bool {
+    entries.iter().any(|entry| normalize(format, entry.clone()).is_ok())
+}
+
+/// Which source contributed each field of a merged entry, by source name.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Provenance {
+    /// Every source that listed the app, in priority order.
+    pub sources: Vec<String>,
+    /// The highest-priority listing, which also supplied the name.
+    pub name: String,
+    pub icon: Option<String>,
+    pub splash: Option<String>,
+    /// One per entry of `aliases`, in the same order.
+    pub aliases: Vec<String>,
+    /// One per entry of `executables`, in the same order.
+    pub executables: Vec<String>,
+}
+
+/// Returned by `get_game_details`.
+#[derive(Debug, Clone, Serialize)]
+pub struct GameDetails {
+    pub game: DetectableApp,
+    pub provenance: Provenance,
+}
+
+/// Merges sources field by field, in priority order. The first listing of an
+/// id supplies its name; icon and splash come from the first source that has
+/// one; aliases and executables are the union over all sources.
+#[derive(Default)]
+pub struct CatalogBuilder {
+    entries: Vec<GameDetails>,
+    index: HashMap<String, usize>,
+}
+
+impl CatalogBuilder {
+    pub fn add(&mut self, source: &str, apps: Vec<DetectableApp>) {
+        for app in apps {
+            match self.index.get(&app.id) {
+                Some(&i) => merge_into(&mut self.entries[i], source, app),
+                None => {
+                    self.index.insert(app.id.clone(), self.entries.len());
+                    self.entries.push(first_listing(source, app));
+                }
+            }
+        }
+    }
+
+    pub fn finish(self) -> Vec<GameDetails> {
+        self.entries
+    }
+}
+
+fn first_listing(source: &str, app: DetectableApp) -> GameDetails {
+    let from = |present: bool| present.then(|| source.to_string());
+    let provenance = Provenance {
+        sources: vec![source.to_string()],
+        name: source.to_string(),
+        icon: from(app.icon.is_some()),
+        splash: from(app.splash.is_some()),
+        aliases: vec![source.to_string(); app.aliases.len()],
+        executables: vec![source.to_string(); app.executables.len()],
+    };
+    GameDetails {
+        game: app,
+        provenance,
+    }
+}
+
+fn merge_into(entry: &mut GameDetails, source: &str, app: DetectableApp) {
+    let game = &mut entry.game;
+    let provenance = &mut entry.provenance;
+    if !provenance.sources.iter().any(|s| s == source) {
+        provenance.sources.push(source.to_string());
+    }
+
+    if game.icon.is_none() && app.icon.is_some() {
+        game.icon = app.icon;
+        provenance.icon = Some(source.to_string());
+    }
+    if game.splash.is_none() && app.splash.is_some() {
+        game.splash = app.splash;
+        provenance.splash = Some(source.to_string());
+    }
+
+    let mut known: HashSet<String> = game.aliases.iter().map(|a| a.to_lowercase()).collect();
+    known.insert(game.name.to_lowercase());
+    for alias in app.aliases.into_iter().chain(std::iter::once(app.name)) {
+        if known.insert(alias.to_lowercase()) {
+            game.aliases.push(alias);
+            provenance.aliases.push(source.to_string());
+        }
+    }
+
+    for exe in app.executables {
+        let existing = game
+            .executables
+            .iter_mut()
+            .find(|e| e.os == exe.os && e.name.eq_ignore_ascii_case(&exe.name));
+        match existing {
+            // Same executable; only fill in what the earlier source left out
+            Some(existing) => {
+                if existing.arguments.is_none() {
+                    existing.arguments = exe.arguments;
+                }
+            }
+            None => {
+                game.executables.push(exe);
+                provenance.executables.push(source.to_string());
+            }
+        }
+    }
+}
+
+/// Payload of `catalog_progress`, emitted as each source of a load finishes.
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogProgress {
+    pub source: String,
+    pub status: SourceStatus,
+    /// Sources finished so far, this one included.
+    pub done: usize,
+    pub total: usize,
+    /// Games in the sources finished so far.
+    pub games: usize,
+    /// Whether those games can already be read. Only the first load of a
+    /// session publishes partial results; a refresh keeps serving the previous
+    /// catalog until it is complete.
+    pub published: bool,
+}
+
+/// Returned by `get_catalog_page`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogPage {
+    pub total: usize,
+    pub offset: usize,
+    pub games: Vec<DetectableApp>,
+}
+
+/// Returned by `get_catalog_counts`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CatalogCounts {
+    pub games: usize,
+    /// Games listed by each source; a game listed by several counts for each.
+    pub by_source: BTreeMap<String, usize>,
+    /// Games with at least one executable for each OS.
+    pub by_os: BTreeMap<String, usize>,
+}
+
+#[derive(Default)]
+struct Loaded {
+    /// In merge order, which is also page order.
+    entries: Vec<GameDetails>,
+    by_id: HashMap<String, usize>,
+    index: SearchIndex,
+}
+
+/// The last catalog loaded by `fetch_game_list`, served in pages, by id and
+/// through `search_games`, and the report of the last refresh, kept for
+/// `get_fetch_report`.
+#[derive(Default)]
+pub struct CatalogStore {
+    loaded: Mutex<Loaded>,
+    report: Mutex<Option<FetchReport>>,
+}
+
+impl CatalogStore {
+    pub fn replace(&self, entries: &[GameDetails]) {
+        let loaded = Loaded {
+            entries: entries.to_vec(),
+            by_id: entries
+                .iter()
+                .enumerate()
+                .map(|(i, e)| (e.game.id.clone(), i))
+                .collect(),
+            index: SearchIndex::build(entries),
+        };
+        if let Ok(mut current) = self.loaded.lock() {
+            *current = loaded;
+        }
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&Loaded) -> T) -> T {
+        match self.loaded.lock() {
+            Ok(loaded) => f(&loaded),
+            Err(e) => f(&e.into_inner()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.with(|l| l.entries.is_empty())
+    }
+
+    pub fn details(&self, id: &str) -> Option<GameDetails> {
+        self.with(|l| l.by_id.get(id).map(|&i| l.entries[i].clone()))
+    }
+
+    pub fn game(&self, id: &str) -> Option<DetectableApp> {
+        self.with(|l| l.by_id.get(id).map(|&i| l.entries[i].game.clone()))
+    }
+
+    pub fn page(&self, offset: usize, limit: usize) -> CatalogPage {
+        self.with(|l| CatalogPage {
+            total: l.entries.len(),
+            offset,
+            games: l
+                .entries
+                .iter()
+                .skip(offset)
+                .take(limit)
+                .map(|e| e.game.clone())
+                .collect(),
+        })
+    }
+
+    pub fn counts(&self) -> CatalogCounts {
+        self.with(|l| {
+            let mut counts = CatalogCounts {
+                games: l.entries.len(),
+                ..Default::default()
+            };
+            for entry in &l.entries {
+                for source in &entry.provenance.sources {
+                    *counts.by_source.entry(source.clone()).or_default() += 1;
+                }
+                let oses: HashSet<&str> = entry.game.executables.iter().map(|e| e.os.as_str()).collect();
+                for os in oses {
+                    *counts.by_os.entry(os.to_string()).or_default() += 1;
+                }
+            }
+            counts
+        })
+    }
+
+    pub fn search(&self, query: &str, offset: usize, limit: usize) -> SearchPage {
+        self.with(|l| l.index.search(query, offset, limit))
+    }
+
+    pub fn set_report(&self, report: FetchReport) {
+        if let Ok(mut current) = self.report.lock() {
+            *current = Some(report);
+        }
+    }
+
+    pub fn report(&self) -> Option<FetchReport> {
+        self.report.lock().ok()?.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn exe(os: &str, name: &str) -> Executable {
+        Executable {
+            os: os.to_string(),
+            name: name.to_string(),
+            is_launcher: false,
+            arguments: None,
+        }
+    }
+
+    #[test]
+    fn discord_entries_take_the_icon_hash() {
+        let app = normalize(
+            SourceFormat::Discord,
+            json!({
+                "id": "356875570916753438",
+                "name": " Minecraft ",
+                "icon_hash": "abc",
+                "executables": [{"os": "WIN32", "name": "minecraft.exe"}, {"os": "linux", "name": " "}],
+            }),
+        )
+        .unwrap();
+        assert_eq!(app.id, "356875570916753438");
+        assert_eq!(app.name, "Minecraft");
+        assert_eq!(app.icon.as_deref(), Some("abc"));
+        assert!(app.aliases.is_empty());
+        assert_eq!(app.executables, vec![exe("win32", "minecraft.exe")]);
+    }
+
+    #[test]
+    fn gist_entries_allow_numeric_ids_and_null_executables() {
+        let app = normalize(
+            SourceFormat::Gist,
+            json!({"id": 42, "name": "Game", "icon": "def", "icon_hash": "old", "executables": null}),
+        )
+        .unwrap();
+        assert_eq!(app.id, "42");
+        assert_eq!(app.icon.as_deref(), Some("def"));
+        assert!(app.executables.is_empty());
+
+        let app = normalize(SourceFormat::Gist, json!({"id": "7", "name": "Other", "icon_hash": "old"})).unwrap();
+        assert_eq!(app.icon.as_deref(), Some("old"));
+    }
+
+    #[test]
+    fn native_entries_are_cleaned() {
+        let app = normalize(
+            SourceFormat::Native,
+            json!({"id": "1", "name": "Game", "aliases": ["game", " Alt ", ""], "icon": " ", "splash": "s"}),
+        )
+        .unwrap();
+        assert_eq!(app.aliases, vec!["Alt"]);
+        assert_eq!(app.icon, None);
+        assert_eq!(app.splash.as_deref(), Some("s"));
+    }
+
+    #[test]
+    fn malformed_entries_are_set_aside() {
+        let entries = vec![
+            json!({"id": "1", "name": "Good"}),
+            json!({"id": "2"}),
+            json!({"id": 3, "name": "  "}),
+            json!("not an object"),
+        ];
+        assert!(any_usable(SourceFormat::Discord, &entries));
+
+        let (apps, malformed) = normalize_list(SourceFormat::Discord, entries);
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].id, "1");
+        let skipped: Vec<(usize, Option<&str>)> = malformed.iter().map(|m| (m.index, m.id.as_deref())).collect();
+        assert_eq!(skipped, vec![(1, Some("2")), (2, Some("3")), (3, None)]);
+        assert_eq!(malformed[1].error, "empty name");
+        assert!(!any_usable(SourceFormat::Discord, &[json!({"id": "2"})]));
+    }
+
+    #[test]
+    fn duplicates_merge_in_priority_order() {
+        let first = DetectableApp {
+            id: "1".to_string(),
+            name: "Game".to_string(),
+            aliases: vec!["The Game".to_string()],
+            icon: None,
+            splash: Some("first-splash".to_string()),
+            executables: vec![exe("win32", "game.exe")],
+        };
+        let second = DetectableApp {
+            id: "1".to_string(),
+            name: "Game: Deluxe".to_string(),
+            aliases: vec!["the game".to_string()],
+            icon: Some("second-icon".to_string()),
+            splash: Some("second-splash".to_string()),
+            executables: vec![
+                Executable {
+                    arguments: Some("--play".to_string()),
+                    ..exe("win32", "GAME.EXE")
+                },
+                exe("linux", "game"),
+            ],
+        };
+        let other = DetectableApp {
+            id: "2".to_string(),
+            ..first.clone()
+        };
+
+        let mut builder = CatalogBuilder::default();
+        builder.add("discord", vec![first, other]);
+        builder.add("gist", vec![second]);
+        let entries = builder.finish();
+        assert_eq!(entries.len(), 2);
+
+        let GameDetails { game, provenance } = &entries[0];
+        assert_eq!(game.name, "Game");
+        assert_eq!(game.icon.as_deref(), Some("second-icon"));
+        assert_eq!(game.splash.as_deref(), Some("first-splash"));
+        assert_eq!(game.aliases, vec!["The Game", "Game: Deluxe"]);
+        assert_eq!(game.executables.len(), 2);
+        assert_eq!(game.executables[0].name, "game.exe");
+        assert_eq!(game.executables[0].arguments.as_deref(), Some("--play"));
+        assert_eq!(game.executables[1], exe("linux", "game"));
+
+        assert_eq!(provenance.sources, vec!["discord", "gist"]);
+        assert_eq!(provenance.name, "discord");
+        assert_eq!(provenance.icon.as_deref(), Some("gist"));
+        assert_eq!(provenance.splash.as_deref(), Some("discord"));
+        assert_eq!(provenance.aliases, vec!["discord", "gist"]);
+        assert_eq!(provenance.executables, vec!["discord", "gist"]);
+
+        assert_eq!(entries[1].provenance.sources, vec!["discord"]);
+        assert_eq!(entries[1].provenance.icon, None);
+    }
+}
@@ -0,0 +1,412 @@
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::catalog::{self, SourceFormat};
+use crate::http::{self, RetryPolicy};
+use crate::sessions::now_millis;
+use crate::storage;
+
+/// How the data for a source (or the whole catalog) was obtained.
+/// Ordered from best to worst, so the catalog reports its worst source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Freshness {
+    /// Downloaded or revalidated with the server just now.
+    Fresh,
+    /// Served from the cache, which is younger than the configured max age.
+    Cached,
+    /// The server could not be reached; this is the last copy that was.
+    Stale,
+}
+
+/// A source's last good response, kept in `catalog-cache/` in the app data dir.
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// Unix time in milliseconds at which the server last confirmed this copy.
+    fetched_at: u64,
+    entries: Vec<Value>,
+}
+
+pub struct Fetched {
+    pub entries: Vec<Value>,
+    pub freshness: Freshness,
+    /// Unix time in milliseconds at which the server last confirmed the data.
+    pub fetched_at: u64,
+    /// Status of the request made for this fetch, if one was made.
+    pub http_status: Option<u16>,
+    /// Problems that did not stop the source from producing entries.
+    pub warnings: Vec<String>,
+}
+
+/// Why a source produced nothing.
+#[derive(Debug, Clone)]
+pub struct SourceError {
+    pub message: String,
+    /// Set when the server answered, but not with a usable list.
+    pub http_status: Option<u16>,
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for SourceError {
+    fn from(message: String) -> Self {
+        SourceError {
+            message,
+            http_status: None,
+        }
+    }
+}
+
+pub struct CatalogCache {
+    dir: Option<PathBuf>,
+}
+
+impl CatalogCache {
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        CatalogCache { dir }
+    }
+
+    pub fn in_app_data<R: tauri::Runtime>(handle: &tauri::AppHandle<R>) -> Self {
+        Self::new(storage::app_data_path(handle, "catalog-cache"))
+    }
+
+    fn entry_path(&self, url: &str) -> Option<PathBuf> {
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{:016x}.json", fnv1a(url.as_bytes()))))
+    }
+
+    fn read(&self, url: &str) -> Option<CacheEntry> {
+        let data = fs::read(self.entry_path(url)?).ok()?;
+        serde_json::from_slice::<CacheEntry>(&data)
+            .ok()
+            .filter(|entry| entry.url == url)
+    }
+
+    fn write(&self, entry: &CacheEntry) {
+        let Some(path) = self.entry_path(&entry.url) else {
+            return;
+        };
+        match serde_json::to_vec(entry) {
+            Ok(json) => {
+                if let Err(e) = storage::write_atomic(&path, &json) {
+                    log::warn!("Failed to cache {}: {}", entry.url, e);
+                }
+            }
+            Err(e) => log::warn!("Failed to cache {}: {}", entry.url, e),
+        }
+    }
+
+    /// Gets the JSON list at `url`. A cached copy younger than `max_age` is
+    /// served as is unless `force` is set; an older one is revalidated with
+    /// its ETag/Last-Modified. If the request fails, the cached copy is served
+    /// as stale, and only without any cached copy is this an error. A list
+    /// with no entry usable in `format` is never cached, so a reshaped
+    /// response can't replace the last good copy. The download, retries
+    /// included, is abandoned at `policy.deadline`.
+    pub async fn fetch(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        format: SourceFormat,
+        max_age: Duration,
+        force: bool,
+        policy: RetryPolicy,
+    ) -> Result<Fetched, SourceError> {
+        let cached = self.read(url);
+        let age_of = |entry: &CacheEntry| Duration::from_millis(now_millis().saturating_sub(entry.fetched_at));
+
+        if let Some(entry) = &cached {
+            if !force && age_of(entry) < max_age {
+                return Ok(Fetched {
+                    entries: entry.entries.clone(),
+                    freshness: Freshness::Cached,
+                    fetched_at: entry.fetched_at,
+                    http_status: None,
+                    warnings: Vec::new(),
+                });
+            }
+        }
+
+        let download = tokio::time::timeout_at(policy.deadline, self.download(client, url, cached.as_ref(), policy));
+        let result = match download.await {
+            Ok(result) => result,
+            Err(_) => Err(SourceError::from(format!("Timed out fetching {}", url))),
+        };
+        let result = match result {
+            Ok((entry, status)) if !catalog::any_usable(format, &entry.entries) => {
+                let message = format!("{} returned no usable entries", url);
+                if cached.is_none() {
+                    // Nothing better to serve; the report shows why it was rejected
+                    return Ok(Fetched {
+                        entries: entry.entries,
+                        freshness: Freshness::Fresh,
+                        fetched_at: entry.fetched_at,
+                        http_status: Some(status),
+                        warnings: vec![message],
+                    });
+                }
+                Err(SourceError {
+                    message,
+                    http_status: Some(status),
+                })
+            }
+            other => other,
+        };
+        match result {
+            Ok((entry, status)) => {
+                self.write(&entry);
+                Ok(Fetched {
+                    entries: entry.entries,
+                    freshness: Freshness::Fresh,
+                    fetched_at: entry.fetched_at,
+                    http_status: Some(status),
+                    warnings: Vec::new(),
+                })
+            }
+            Err(e) => {
+                let Some(entry) = cached else {
+                    return Err(e);
+                };
+                log::info!("Serving cached copy of {}: {}", url, e);
+                let freshness = if age_of(&entry) < max_age {
+                    Freshness::Cached
+                } else {
+                    Freshness::Stale
+                };
+                Ok(Fetched {
+                    entries: entry.entries,
+                    freshness,
+                    fetched_at: entry.fetched_at,
+                    http_status: e.http_status,
+                    warnings: vec![e.message],
+                })
+            }
+        }
+    }
+
+    async fn download(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        cached: Option<&CacheEntry>,
+        policy: RetryPolicy,
+    ) -> Result<(CacheEntry, u16), SourceError> {
+        let mut request = client.get(url);
+        if let Some(entry) = cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &entry.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let res = http::send_with_retry(request, policy)
+            .await
+            .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+
+        let status = res.status();
+        if status == StatusCode::NOT_MODIFIED {
+            if let Some(entry) = cached {
+                let entry = CacheEntry {
+                    url: url.to_string(),
+                    etag: entry.etag.clone(),
+                    last_modified: entry.last_modified.clone(),
+                    fetched_at: now_millis(),
+                    entries: entry.entries.clone(),
+                };
+                return Ok((entry, status.as_u16()));
+            }
+        }
+        let failed = |message: String| SourceError {
+            message,
+            http_status: Some(status.as_u16()),
+        };
+        if !status.is_success() {
+            return Err(failed(format!("{} returned {}", url, status)));
+        }
+
+        let header = |name| {
+            res.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        let entries = res
+            .json::<Vec<Value>>()
+            .await
+            .map_err(|e| failed(format!("Invalid catalog from {}: {}", url, e)))?;
+
+        let entry = CacheEntry {
+            url: url.to_string(),
+            etag,
+            last_modified,
+            fetched_at: now_millis(),
+            entries,
+        };
+        Ok((entry, status.as_u16()))
+    }
+}
+
+/// Stable file name for a URL; `DefaultHasher` may change between Rust releases.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const GAMES: &str = r#"[{"id": "1", "name": "One"}, {"id": "2", "name": "Two"}]"#;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("qp-catalog-cache-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn response(status: &str, headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            headers,
+            body.len(),
+            body
+        )
+    }
+
+    /// Answers one request per response, in order, and keeps the requests
+    /// it got, lowercased.
+    async fn serve(responses: Vec<String>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/games.json", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0; 8192];
+                let n = socket.read(&mut buf).await.unwrap();
+                seen.lock().unwrap().push(String::from_utf8_lossy(&buf[..n]).to_lowercase());
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 0,
+            deadline: tokio::time::Instant::now() + Duration::from_secs(10),
+        }
+    }
+
+    async fn fetch(cache: &CatalogCache, url: &str, max_age: Duration) -> Result<Fetched, SourceError> {
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        cache.fetch(&client, url, SourceFormat::Discord, max_age, false, policy()).await
+    }
+
+    #[tokio::test]
+    async fn revalidation_keeps_the_cached_entries() {
+        let (url, requests) = serve(vec![
+            response("200 OK", "ETag: \"v1\"\r\n", GAMES),
+            response("304 Not Modified", "", ""),
+        ])
+        .await;
+        let cache = CatalogCache::new(Some(scratch("revalidate")));
+
+        let first = fetch(&cache, &url, Duration::ZERO).await.unwrap();
+        assert_eq!(first.freshness, Freshness::Fresh);
+        assert_eq!(first.entries.len(), 2);
+
+        let second = fetch(&cache, &url, Duration::ZERO).await.unwrap();
+        assert_eq!(second.freshness, Freshness::Fresh);
+        assert_eq!(second.http_status, Some(304));
+        assert_eq!(second.entries.len(), 2);
+        assert!(requests.lock().unwrap()[1].contains("if-none-match: \"v1\""));
+    }
+
+    #[tokio::test]
+    async fn young_copies_are_served_without_asking() {
+        let (url, requests) = serve(vec![response("200 OK", "", GAMES)]).await;
+        let cache = CatalogCache::new(Some(scratch("young")));
+
+        fetch(&cache, &url, Duration::from_secs(60)).await.unwrap();
+        let cached = fetch(&cache, &url, Duration::from_secs(60)).await.unwrap();
+        assert_eq!(cached.freshness, Freshness::Cached);
+        assert_eq!(cached.entries.len(), 2);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failures_fall_back_to_the_stale_copy() {
+        let (url, _) = serve(vec![
+            response("200 OK", "", GAMES),
+            response("500 Internal Server Error", "", ""),
+        ])
+        .await;
+        let cache = CatalogCache::new(Some(scratch("stale")));
+
+        fetch(&cache, &url, Duration::ZERO).await.unwrap();
+        let stale = fetch(&cache, &url, Duration::ZERO).await.unwrap();
+        assert_eq!(stale.freshness, Freshness::Stale);
+        assert_eq!(stale.http_status, Some(500));
+        assert_eq!(stale.entries.len(), 2);
+        assert_eq!(stale.warnings.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failures_without_a_copy_are_errors() {
+        let (url, _) = serve(vec![response("503 Service Unavailable", "", "")]).await;
+        let cache = CatalogCache::new(Some(scratch("uncached")));
+
+        let error = fetch(&cache, &url, Duration::ZERO).await.err().unwrap();
+        assert_eq!(error.http_status, Some(503));
+    }
+
+    #[tokio::test]
+    async fn unusable_lists_never_replace_the_last_good_copy() {
+        let reshaped = r#"[{"application": {"id": "1"}}]"#;
+        let (url, _) = serve(vec![
+            response("200 OK", "", GAMES),
+            response("200 OK", "", reshaped),
+            response("200 OK", "", reshaped),
+        ])
+        .await;
+        let cache = CatalogCache::new(Some(scratch("unusable")));
+
+        fetch(&cache, &url, Duration::ZERO).await.unwrap();
+        let kept = fetch(&cache, &url, Duration::ZERO).await.unwrap();
+        assert_eq!(kept.freshness, Freshness::Stale);
+        assert_eq!(kept.entries.len(), 2);
+        assert_eq!(cache.read(&url).unwrap().entries.len(), 2);
+
+        // Without a good copy the list is passed on, but still not cached
+        let fresh = CatalogCache::new(Some(scratch("unusable-uncached")));
+        let passed_on = fetch(&fresh, &url, Duration::ZERO).await.unwrap();
+        assert_eq!(passed_on.entries.len(), 1);
+        assert_eq!(passed_on.warnings.len(), 1);
+        assert!(fresh.read(&url).is_none());
+    }
+}
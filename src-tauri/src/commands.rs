@@ -1,185 +1,551 @@
-use tauri::{AppHandle, Manager, Emitter};
-use std::env;
-use std::path::Path;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
 use tauri::path::BaseDirectory;
 
+use crate::catalog::{Catalog, CatalogCounts, CatalogPage, CatalogProgress, CatalogStore, DetectableApp, GameDetails};
+use crate::diagnostics::FetchReport;
+use crate::dummy_games::{self, DummyGame, Removal};
+use crate::http::{HttpClient, RetryPolicy};
+use crate::catalog_cache::CatalogCache;
+use crate::control::{self, ControlReply};
+use crate::executables::{self, HostOs, ResolvedExecutable};
+use crate::game_paths::{self, GamePaths, GamesRoot};
+use crate::process::{self, ProcessError, RunnerProcess};
+use crate::queue::{self, QueueGame, QueueItem, QueueItemId, QueueManager, QueueSnapshot};
+use crate::settings::{Settings, SettingsStore};
+use crate::sources::{self, FetchContext};
+use crate::sessions::{self, ChangeReason, Session, SessionId, SessionRegistry, SessionStatus};
+use crate::runner_copy;
+use crate::runner_status;
+use crate::storage;
+use crate::search::{self, SearchPage};
+
 #[tauri::command(rename_all = "snake_case")]
-pub async fn create_dummy_game(
-    handle: AppHandle,
+pub async fn create_dummy_game<R: Runtime>(
+    handle: AppHandle<R>,
     path: &str, // Relative path inside 'games' folder
     executable_name: &str,
     app_id: String,
 ) -> Result<String, String> {
-    // Get the executable directory to look for config file
-    let exe_path = env::current_exe().map_err(|e| e.to_string())?;
-    let exe_dir = exe_path.parent().unwrap_or_else(|| Path::new(""));
-
-    let normalized_path = Path::new(path).to_string_lossy().to_string();
+    let target_executable_path = install_runner(&handle, path, executable_name, &app_id)?;
+    Ok(format!(
+        "Dummy executable copied to: {:?}",
+        target_executable_path
+    ))
+}
 
-    let game_folder_path = exe_dir
-        .join("games")
-        .join(&app_id)
-        .join(normalized_path);
+/// Copies the runner into the game folder for `app_id` and returns where it went.
+pub(crate) fn install_runner<R: Runtime>(
+    handle: &AppHandle<R>,
+    path: &str,
+    executable_name: &str,
+    app_id: &str,
+) -> Result<PathBuf, String> {
+    // Every caller-supplied part is validated; the folders are created here
+    let games = GamePaths::for_app(handle).map_err(|e| e.to_string())?;
+    let target_executable_path = games
+        .prepare(app_id, path, executable_name)
+        .map_err(|e| format!("Invalid game path: {}", e))?;
 
-    println!("Creating game folder: {:?}", game_folder_path);
+    let runner = runner_copy::bundled(handle)?;
+    let game_dir = games.game_dir(app_id).map_err(|e| e.to_string())?;
+    // Only our own copies are ever replaced
+    if !dummy_games::is_replaceable(&game_dir, &target_executable_path, Some(&runner.sha256)) {
+        return Err(format!(
+            "{:?} already exists and is not a runner copy; move or delete it first",
+            target_executable_path
+        ));
+    }
 
-    if let Err(e) = std::fs::create_dir_all(&game_folder_path) {
-        return Err(format!("Failed to create game folder: {}", e));
+    let method = match runner_copy::place(&runner, games.root(), &target_executable_path) {
+        Ok(method) => method,
+        Err(e) => return Err(format!("Failed to copy dummy executable: {}", e)),
+    };
+    log::info!("Placed runner at {:?} ({:?})", target_executable_path, method);
+    // Remember the copy so cleanup can tell it apart from anything else
+    let name = handle.state::<CatalogStore>().game(app_id).map(|g| g.name);
+    let recorded =
+        dummy_games::record_install(&game_dir, app_id, name.as_deref(), &target_executable_path, &runner, method);
+    if let Err(e) = recorded {
+        log::warn!("Failed to record runner copy {:?}: {}", target_executable_path, e);
     }
+    Ok(target_executable_path)
+}
 
-    // Resolve the runner executable from resources
+/// The runner executable bundled with the app for the platform it was built
+/// for, `resources/runner-<target triple>` (plus `.exe` on Windows).
+pub(crate) fn runner_resource<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
     let resource_path = handle
         .path()
-        .resolve("resources/runner.exe", BaseDirectory::Resource)
+        .resolve(env!("RUNNER_RESOURCE"), BaseDirectory::Resource)
         .map_err(|e| e.to_string())?;
 
     if !resource_path.exists() {
-         return Err(format!("Runner executable not found at: {:?}", resource_path));
-    }
-
-    let target_executable_path = game_folder_path.join(executable_name);
-    
-    // Ensure parent directory for the executable exists (handling cases like 'win64/game.exe')
-    if let Some(parent) = target_executable_path.parent() {
-        if !parent.exists() {
-            println!("Creating executable parent directory: {:?}", parent);
-            if let Err(e) = std::fs::create_dir_all(parent) {
-                return Err(format!("Failed to create executable parent directory: {}", e));
-            }
-        }
-    }
-
-    println!("Copying runner from {:?} to {:?}", resource_path, target_executable_path);
-
-    match std::fs::copy(&resource_path, &target_executable_path) {
-        Ok(_) => Ok(format!(
-            "Dummy executable copied to: {:?}",
-            target_executable_path
-        )),
-        Err(e) => Err(format!("Failed to copy dummy executable: {}", e)),
+        return Err(format!(
+            "No runner is bundled for {} (expected {:?}), so dummy games can't be created on this platform",
+            env!("RUNNER_TARGET"),
+            resource_path
+        ));
     }
+    Ok(resource_path)
 }
 
 #[tauri::command(rename_all = "snake_case")]
-pub async fn start_game_process(
-    handle: AppHandle,
+pub async fn start_game_process<R: Runtime>(
+    handle: AppHandle<R>,
     name: &str,
     path: &str,
     executable_name: &str,
     app_id: String,
     icon_url: Option<String>,
-) -> Result<String, String> {
-    let exe_path = env::current_exe().map_err(|e| e.to_string())?;
-    let exe_dir = exe_path.parent().unwrap_or_else(|| Path::new(""));
-
-    let normalized_path = Path::new(path).to_string_lossy().to_string();
-
-    let game_folder_path = exe_dir
-        .join("games")
-        .join(&app_id)
-        .join(normalized_path);
-        
-    let executable_path = game_folder_path.join(executable_name);
+    duration: Option<u64>,
+) -> Result<Session, String> {
+    spawn_runner(&handle, name, path, executable_name, app_id, icon_url, duration)
+}
 
+/// Launches an installed runner and registers it as a session. A monitor task
+/// records its exit and tells the queue about it.
+pub(crate) fn spawn_runner<R: Runtime>(
+    handle: &AppHandle<R>,
+    name: &str,
+    path: &str,
+    executable_name: &str,
+    app_id: String,
+    icon_url: Option<String>,
+    duration: Option<u64>,
+) -> Result<Session, String> {
+    let games = GamePaths::for_app(handle).map_err(|e| e.to_string())?;
+    let executable_path = games
+        .executable(&app_id, path, executable_name)
+        .map_err(|e| format!("Invalid game path: {}", e))?;
     if !executable_path.exists() {
         return Err(format!("Executable not found at {:?}", executable_path));
     }
+    let executable_path = games
+        .existing(&app_id, path, executable_name)
+        .map_err(|e| format!("Invalid game path: {}", e))?;
+    let game_folder_path = games
+        .folder(&app_id, path)
+        .map_err(|e| format!("Invalid game path: {}", e))?;
 
-    println!("Starting process: {:?}", executable_path);
-
-    let mut args = vec!["--title".to_string(), name.to_string()];
-    if let Some(url) = icon_url {
-        args.push("--icon".to_string());
-        args.push(url);
+    log::info!("Starting process: {:?}", executable_path);
+    if let Ok(game_dir) = games.game_dir(&app_id) {
+        if let Err(e) = dummy_games::record_use(&game_dir, &app_id, name) {
+            log::warn!("Failed to record use of {:?}: {}", game_dir, e);
+        }
     }
 
-    let mut child = std::process::Command::new(&executable_path)
-        .args(&args)
-        .current_dir(game_folder_path)
+    let control_path = control::new_socket_path();
+    let mut child = runner_command(&executable_path, &game_folder_path, name, icon_url, duration, &control_path)
         .spawn()
         .map_err(|e| format!("Failed to start process: {}", e))?;
 
-    let pid = child.id();
+    let stdout = child.stdout.take();
+    let process = Arc::new(RunnerProcess::new(child));
     let app_handle = handle.clone();
 
-    // Spawn a monitoring task
-    tauri::async_runtime::spawn(async move {
-        // Simple wait (blocking the thread, but it's a spawned thread so it's okay for now, 
-        // ideally we'd use async process wait but std::process is sync. 
-        // Tauri's spawn sends it to a thread pool.)
-        // check status periodically or use wait() which blocks this thread.
-        match child.wait() {
+    let session = handle.state::<SessionRegistry>().insert(
+        app_id,
+        name.to_string(),
+        process.clone(),
+        executable_path,
+        control_path,
+    );
+    sessions::emit_change(handle, session.clone(), ChangeReason::Started);
+
+    if let Some(stdout) = stdout {
+        let status_handle = handle.clone();
+        let status_session = session.clone();
+        std::thread::spawn(move || {
+            runner_status::forward(&status_handle, &status_session, stdout);
+        });
+    }
+
+    // Wait for the exit on a thread of its own, so a long-running game never
+    // holds one of the runtime's workers, then hand back to the runtime
+    let session_id = session.id;
+    let pid = session.pid;
+    let control_path = session.control_path.clone();
+    std::thread::spawn(move || {
+        let exit_code = match process.wait() {
             Ok(status) => {
-                println!("Process {} exited with status: {}", pid, status);
-                let _ = app_handle.emit("game_exited", ());
+                log::info!("Process {} exited with status: {}", pid, status);
+                status.code()
             }
             Err(e) => {
-                eprintln!("Failed to wait on process {}: {}", pid, e);
+                log::warn!("Failed to wait on process {}: {}", pid, e);
+                None
             }
-        }
+        };
+        tauri::async_runtime::spawn(async move {
+            runner_exited(&app_handle, session_id, &control_path, exit_code).await;
+        });
     });
 
-    Ok("Process started successfully".to_string())
+    Ok(session)
 }
 
-#[tauri::command(rename_all = "snake_case")]
-pub async fn stop_process(exec_name: String) -> Result<(), String> {
-    let output = std::process::Command::new("taskkill")
-        .arg("/F")
-        .arg("/IM")
-        .arg(exec_name)
-        .output()
-        .map_err(|e| format!("Failed to execute taskkill: {}", e))?;
+/// The command line a runner copy is started with. It runs in `folder`,
+/// writes status events to a piped stdout and listens on `control_path`.
+pub fn runner_command(
+    executable_path: &Path,
+    folder: &Path,
+    name: &str,
+    icon_url: Option<String>,
+    duration: Option<u64>,
+    control_path: &Path,
+) -> Command {
+    let mut command = Command::new(executable_path);
+    command.arg("--title").arg(name);
+    if let Some(url) = icon_url {
+        command.arg("--icon").arg(url);
+    }
+    if let Some(secs) = duration {
+        command.arg("--duration").arg(secs.to_string());
+    }
+    // Let the runner end itself if Quest Passer goes away without stopping it
+    command.arg("--parent-pid").arg(std::process::id().to_string());
+    // Report progress as JSON lines on stdout
+    command.arg("--status");
+    // Accept graceful stop and live updates over a per-session socket
+    command.arg("--control").arg(control_path);
+    command.current_dir(folder).stdout(Stdio::piped());
+    command
+}
+
+/// Records that a session's runner is gone and lets the queue move on.
+pub(crate) async fn runner_exited<R: Runtime>(
+    handle: &AppHandle<R>,
+    session_id: SessionId,
+    control_path: &Path,
+    exit_code: Option<i32>,
+) {
+    // The runner normally removes its socket, unless it was killed
+    let _ = std::fs::remove_file(control_path);
 
-    if output.status.success() {
-        Ok(())
-    } else {
-        Err(format!(
-            "Failed to stop process: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ))
+    let registry = handle.state::<SessionRegistry>();
+    let mut reason = ChangeReason::Exited;
+    let updated = registry.update(session_id, |s| {
+        s.exit_code = exit_code;
+        s.status = if s.status == SessionStatus::Stopping {
+            reason = ChangeReason::Stopped;
+            SessionStatus::Stopped
+        } else {
+            SessionStatus::Exited
+        };
+    });
+    if let Some(updated) = updated {
+        sessions::emit_change(handle, updated, reason);
     }
+    queue::session_ended(handle, session_id).await;
+}
+
+fn control_path(sessions: &SessionRegistry, app_id: &str) -> Result<std::path::PathBuf, String> {
+    sessions
+        .active_for(app_id)
+        .map(|s| s.control_path)
+        .ok_or_else(|| ProcessError::NotRunning(app_id.to_string()).to_string())
 }
 
 #[tauri::command(rename_all = "snake_case")]
-pub async fn fetch_game_list() -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let mut games: Vec<serde_json::Value> = Vec::new();
+pub fn list_sessions(sessions: State<'_, SessionRegistry>) -> Vec<Session> {
+    sessions.list()
+}
 
-    // 1. Fetch from Discord API
-    let discord_url = "https://discord.com/api/applications/detectable";
-    if let Ok(res) = client.get(discord_url).send().await {
-        if let Ok(list) = res.json::<Vec<serde_json::Value>>().await {
-            games.extend(list);
-        }
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_session(sessions: State<'_, SessionRegistry>, session_id: SessionId) -> Result<Session, String> {
+    sessions
+        .get(session_id)
+        .ok_or_else(|| format!("No session with id {}", session_id))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_runner_status(
+    sessions: State<'_, SessionRegistry>,
+    app_id: String,
+) -> Result<ControlReply, String> {
+    let path = control_path(&sessions, &app_id)?;
+    control::send(&path, "status").await
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn set_game_title(
+    sessions: State<'_, SessionRegistry>,
+    app_id: String,
+    title: String,
+) -> Result<(), String> {
+    let path = control_path(&sessions, &app_id)?;
+    control::send(&path, &format!("set-title {}", title)).await?;
+    Ok(())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn extend_game_duration(
+    sessions: State<'_, SessionRegistry>,
+    app_id: String,
+    seconds: u64,
+) -> Result<u64, String> {
+    let path = control_path(&sessions, &app_id)?;
+    let reply = control::send(&path, &format!("extend-duration {}", seconds)).await?;
+    Ok(reply.duration.unwrap_or_default())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn stop_process<R: Runtime>(
+    handle: AppHandle<R>,
+    sessions: State<'_, SessionRegistry>,
+    app_id: String,
+) -> Result<(), ProcessError> {
+    let session = sessions
+        .active_for(&app_id)
+        .ok_or_else(|| ProcessError::NotRunning(app_id.clone()))?;
+    stop_session(&handle, &session).await
+}
+
+/// Stops one session: the control socket first, then the runner we spawned.
+pub(crate) async fn stop_session<R: Runtime>(handle: &AppHandle<R>, session: &Session) -> Result<(), ProcessError> {
+    let sessions = handle.state::<SessionRegistry>();
+    if let Some(stopping) = sessions.update(session.id, |s| s.status = SessionStatus::Stopping) {
+        sessions::emit_change(handle, stopping, ChangeReason::Stopping);
     }
 
-    // 2. Fetch from Extended Gist
-    let gist_url = "https://gist.githubusercontent.com/DeadSix27/b8e377c9fed6d98bff22dcdf8807e207/raw/52d1f2d31be7168a0486a3a355e06a2d751bdc44/gameslist.json";
-    if let Ok(res) = client.get(gist_url).send().await {
-        if let Ok(list) = res.json::<Vec<serde_json::Value>>().await {
-            games.extend(list);
-        }
+    // Ask the runner to exit on its own first; it is only ever killed through
+    // its own child handle, never by a PID that may have been reused
+    match control::send(&session.control_path, "stop").await {
+        Ok(_) if session.process.wait_for_exit(process::GRACE_PERIOD).await => return Ok(()),
+        Ok(_) => log::warn!("Runner {} acknowledged stop but is still running", session.pid),
+        Err(e) => log::warn!("Graceful stop of runner {} failed: {}", session.pid, e),
     }
 
-    // 3. Deduplicate by ID
-    let mut unique_games = Vec::new();
-    let mut seen_ids = std::collections::HashSet::new();
+    session.process.terminate().await
+}
 
-    for mut game in games {
-        // Normalize 'icon_hash' to 'icon' for consistency across sources
-        if game.get("icon").is_none() {
-            if let Some(hash) = game.get("icon_hash").and_then(|h| h.as_str()) {
-                game["icon"] = serde_json::Value::String(hash.to_string());
-            }
-        }
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_queue(queue: State<'_, QueueManager>) -> QueueSnapshot {
+    queue.snapshot()
+}
 
-        if let Some(id) = game.get("id").and_then(|v| v.as_str()) {
-            if seen_ids.insert(id.to_string()) {
-                unique_games.push(game);
-            }
+#[tauri::command(rename_all = "snake_case")]
+pub fn queue_enqueue<R: Runtime>(
+    handle: AppHandle<R>,
+    queue: State<'_, QueueManager>,
+    game: QueueGame,
+) -> Result<QueueItem, String> {
+    // Refuse a bad path now rather than when its turn comes
+    game_paths::validate_app_id(&game.app_id)
+        .and(game_paths::validate_executable_name(&game.executable_name))
+        .map_err(|e| format!("Invalid game path: {}", e))?;
+    let item = queue.enqueue(game)?;
+    queue::emit_changed(&handle);
+    Ok(item)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn queue_remove<R: Runtime>(
+    handle: AppHandle<R>,
+    queue: State<'_, QueueManager>,
+    item_id: QueueItemId,
+) -> Result<(), String> {
+    queue.remove(item_id)?;
+    queue::emit_changed(&handle);
+    Ok(())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn queue_reorder<R: Runtime>(
+    handle: AppHandle<R>,
+    queue: State<'_, QueueManager>,
+    item_ids: Vec<QueueItemId>,
+) -> Result<(), String> {
+    queue.reorder(&item_ids)?;
+    queue::emit_changed(&handle);
+    Ok(())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn queue_clear<R: Runtime>(handle: AppHandle<R>, queue: State<'_, QueueManager>) {
+    queue.clear();
+    queue::emit_changed(&handle);
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn queue_start<R: Runtime>(handle: AppHandle<R>) -> Result<(), String> {
+    queue::start(&handle).await;
+    Ok(())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn queue_pause<R: Runtime>(handle: AppHandle<R>, queue: State<'_, QueueManager>) -> Result<(), String> {
+    queue.pause()?;
+    queue::emit_changed(&handle);
+    Ok(())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn queue_resume<R: Runtime>(handle: AppHandle<R>) -> Result<(), String> {
+    queue::resume(&handle).await
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn queue_skip<R: Runtime>(handle: AppHandle<R>) -> Result<(), String> {
+    queue::skip(&handle).await
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn queue_set_item_duration<R: Runtime>(handle: AppHandle<R>, seconds: u64) -> Result<(), String> {
+    queue::set_item_duration(&handle, seconds).await
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_settings(settings: State<'_, SettingsStore>) -> Settings {
+    settings.get()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn update_settings(
+    settings: State<'_, SettingsStore>,
+    http: State<'_, HttpClient>,
+    new_settings: Settings,
+) -> Result<Settings, String> {
+    // Reject a bad proxy or certificate before it is saved
+    http.configure(&new_settings.http)?;
+    settings.set(new_settings)?;
+    Ok(settings.get())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn fetch_game_list<R: Runtime>(
+    handle: AppHandle<R>,
+    settings: State<'_, SettingsStore>,
+    http: State<'_, HttpClient>,
+    force_refresh: Option<bool>,
+) -> Result<Catalog, String> {
+    let settings = settings.get();
+    let data_dir = storage::data_dir(&handle);
+    let ctx = FetchContext {
+        client: http.get(),
+        cache: CatalogCache::in_app_data(&handle),
+        max_age: settings.catalog_max_age(),
+        force: force_refresh.unwrap_or(false),
+        policy: RetryPolicy {
+            max_retries: settings.http.max_retries,
+            deadline: tokio::time::Instant::now() + settings.fetch_deadline(),
+        },
+    };
+    let sources = sources::enabled(&settings.catalog_sources, data_dir.as_deref());
+    let store = handle.state::<CatalogStore>();
+    let publish_partial = store.is_empty();
+    let (loaded, report) = sources::load_catalog(&sources, Arc::new(ctx), |progress| {
+        if publish_partial {
+            store.replace(progress.partial);
         }
-    }
+        let _ = handle.emit(
+            "catalog_progress",
+            CatalogProgress {
+                source: progress.report.name.clone(),
+                status: progress.report.status,
+                done: progress.done,
+                total: progress.total,
+                games: progress.partial.len(),
+                published: publish_partial,
+            },
+        );
+    })
+    .await;
+    store.set_report(report);
+    let loaded = loaded?;
+    store.replace(&loaded.details);
+    Ok(loaded.catalog)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_fetch_report(catalog: State<'_, CatalogStore>) -> Option<FetchReport> {
+    catalog.report()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_catalog_page(catalog: State<'_, CatalogStore>, offset: Option<usize>, limit: Option<usize>) -> CatalogPage {
+    let limit = limit.unwrap_or(search::DEFAULT_PAGE_SIZE).clamp(1, search::MAX_PAGE_SIZE);
+    catalog.page(offset.unwrap_or(0), limit)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_catalog_counts(catalog: State<'_, CatalogStore>) -> CatalogCounts {
+    catalog.counts()
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_game(catalog: State<'_, CatalogStore>, app_id: String) -> Result<DetectableApp, String> {
+    catalog
+        .game(&app_id)
+        .ok_or_else(|| format!("No game with id {} in the catalog", app_id))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_game_details(catalog: State<'_, CatalogStore>, app_id: String) -> Result<GameDetails, String> {
+    catalog
+        .details(&app_id)
+        .ok_or_else(|| format!("No game with id {} in the catalog", app_id))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn search_games(
+    catalog: State<'_, CatalogStore>,
+    query: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> SearchPage {
+    catalog.search(&query, offset.unwrap_or(0), limit.unwrap_or(search::DEFAULT_PAGE_SIZE))
+}
+
+/// Ranks the files a game can be started as on `os` (the host OS by default).
+/// Games missing from the catalog, such as custom ones, need `fallback_name`
+/// and get a file named after it.
+#[tauri::command(rename_all = "snake_case")]
+pub fn resolve_executable(
+    catalog: State<'_, CatalogStore>,
+    app_id: String,
+    os: Option<HostOs>,
+    fallback_name: Option<String>,
+) -> Result<ResolvedExecutable, String> {
+    let os = os.unwrap_or_else(HostOs::current);
+    let game = match (catalog.game(&app_id), fallback_name) {
+        (Some(game), _) => game,
+        (None, Some(name)) => DetectableApp {
+            id: app_id,
+            name,
+            aliases: Vec::new(),
+            icon: None,
+            splash: None,
+            executables: Vec::new(),
+        },
+        (None, None) => return Err(format!("No game with id {} in the catalog", app_id)),
+    };
+    Ok(executables::resolve(&game, os))
+}
 
-    serde_json::to_string(&unique_games).map_err(|e| e.to_string())
+#[tauri::command(rename_all = "snake_case")]
+pub fn get_games_root<R: Runtime>(handle: AppHandle<R>) -> Result<GamesRoot, String> {
+    game_paths::games_root(&handle).map_err(|e| e.to_string())
+}
+
+// Both hash every file they look at, so the work runs on a blocking thread
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn list_dummy_games<R: Runtime>(handle: AppHandle<R>) -> Result<Vec<DummyGame>, String> {
+    tauri::async_runtime::spawn_blocking(move || dummy_games::list(&handle))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn remove_dummy_games<R: Runtime>(
+    handle: AppHandle<R>,
+    app_ids: Vec<String>,
+) -> Result<Vec<Removal>, String> {
+    tauri::async_runtime::spawn_blocking(move || dummy_games::remove(&handle, &app_ids))
+        .await
+        .map_err(|e| e.to_string())?
 }
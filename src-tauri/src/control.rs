@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+const CONTROL_TIMEOUT: Duration = Duration::from_secs(3);
+
+static NEXT_SOCKET: AtomicU32 = AtomicU32::new(0);
+
+/// Reply to a control command (see `runner/src/control.rs`). Only `status`
+/// fills in every field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlReply {
+    pub ok: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub pid: Option<u32>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub elapsed: Option<u64>,
+    #[serde(default)]
+    pub duration: Option<u64>,
+}
+
+/// Picks a fresh per-session socket path. Prefers `$XDG_RUNTIME_DIR`, which is
+/// private to the user, over the shared temp directory.
+pub fn new_socket_path() -> PathBuf {
+    let dir = env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|d| d.is_dir())
+        .unwrap_or_else(env::temp_dir);
+    let n = NEXT_SOCKET.fetch_add(1, Ordering::Relaxed);
+    dir.join(format!("questpasser-{}-{}.sock", std::process::id(), n))
+}
+
+/// Sends one command line to the runner listening at `path` and waits for its reply.
+pub async fn send(path: &Path, command: &str) -> Result<ControlReply, String> {
+    // The protocol is line based, so a stray newline would split the command.
+    let command = command.replace(['\r', '\n'], " ");
+    let reply = tokio::time::timeout(CONTROL_TIMEOUT, exchange(path, &command))
+        .await
+        .map_err(|_| format!("Runner did not answer '{}' in time", command))??;
+
+    if reply.ok {
+        Ok(reply)
+    } else {
+        Err(reply
+            .error
+            .unwrap_or_else(|| format!("Runner rejected '{}'", command)))
+    }
+}
+
+async fn exchange(path: &Path, command: &str) -> Result<ControlReply, String> {
+    let stream = connect(path)
+        .await
+        .map_err(|e| format!("Failed to connect to runner at {:?}: {}", path, e))?;
+    let (reader, mut writer) = tokio::io::split(stream);
+
+    writer
+        .write_all(format!("{}\n", command).as_bytes())
+        .await
+        .map_err(|e| format!("Failed to send command to runner: {}", e))?;
+
+    let mut line = String::new();
+    BufReader::new(reader)
+        .read_line(&mut line)
+        .await
+        .map_err(|e| format!("Failed to read runner reply: {}", e))?;
+
+    serde_json::from_str(&line).map_err(|e| format!("Invalid runner reply {:?}: {}", line, e))
+}
+
+#[cfg(unix)]
+async fn connect(path: &Path) -> std::io::Result<tokio::net::UnixStream> {
+    tokio::net::UnixStream::connect(path).await
+}
+
+#[cfg(windows)]
+async fn connect(path: &Path) -> std::io::Result<tokio::net::TcpStream> {
+    // On Windows the runner writes its loopback address into the control path.
+    let addr = tokio::fs::read_to_string(path).await?;
+    tokio::net::TcpStream::connect(addr.trim()).await
+}
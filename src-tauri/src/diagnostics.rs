@@ -0,0 +1,75 @@
+use serde::Serialize;
+
+use crate::catalog::{MalformedEntry, SourceFormat};
+
+/// Malformed entries kept per source; enough to see what changed upstream.
+pub const MALFORMED_SAMPLES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceStatus {
+    Fresh,
+    Cached,
+    /// Served from the cache because the source could not be refreshed.
+    Stale,
+    /// Produced nothing.
+    Failed,
+}
+
+/// How one source did during a catalog refresh.
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceReport {
+    pub name: String,
+    /// URL or path.
+    pub location: String,
+    pub format: SourceFormat,
+    pub status: SourceStatus,
+    pub http_status: Option<u16>,
+    pub latency_ms: u64,
+    /// Entries the source listed.
+    pub entries: usize,
+    /// Entries that normalised into a game.
+    pub accepted: usize,
+    pub malformed: usize,
+    pub malformed_samples: Vec<MalformedEntry>,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl SourceReport {
+    /// One line per source in the app log.
+    pub fn log(&self) {
+        let http = self
+            .http_status
+            .map(|code| format!(" http={}", code))
+            .unwrap_or_default();
+        log::info!(
+            "[catalog] {} {:?}{} {}ms entries={} accepted={} malformed={}{}",
+            self.name,
+            self.status,
+            http,
+            self.latency_ms,
+            self.entries,
+            self.accepted,
+            self.malformed,
+            self.error
+                .as_ref()
+                .map(|e| format!(" error={}", e))
+                .unwrap_or_default()
+        );
+        for warning in &self.warnings {
+            log::warn!("[catalog] {} warning: {}", self.name, warning);
+        }
+    }
+}
+
+/// Returned by `get_fetch_report`: what the last catalog refresh did.
+#[derive(Debug, Clone, Serialize)]
+pub struct FetchReport {
+    /// Unix time in milliseconds.
+    pub started_at: u64,
+    pub duration_ms: u64,
+    /// Games in the merged catalog.
+    pub games: usize,
+    pub sources: Vec<SourceReport>,
+}
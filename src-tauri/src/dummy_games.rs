@@ -0,0 +1,547 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::catalog::CatalogStore;
+use crate::game_paths::{self, GamePaths};
+use crate::queue::QueueManager;
+use crate::runner_copy::{self, PlaceMethod, Runner};
+use crate::sessions::{now_millis, SessionRegistry};
+use crate::settings::SettingsStore;
+use crate::storage;
+
+/// Kept in each game folder, listing the runner copies made there.
+pub const MANIFEST_FILE: &str = "quest-passer.json";
+/// Debug log older Windows runners wrote into the folder they ran in.
+const RUNNER_LOG_FILE: &str = "runner_debug.txt";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    app_id: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    files: Vec<RunnerFile>,
+    /// Unix time in milliseconds of the last start.
+    #[serde(default)]
+    last_used: Option<u64>,
+}
+
+/// A runner copy, as it was when it was made.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RunnerFile {
+    /// Relative to the game folder, with `/` between folders.
+    path: String,
+    size: u64,
+    sha256: String,
+    /// Whether it was linked to the stored copy in `.runner` rather than
+    /// copied, so the two share their data.
+    #[serde(default)]
+    linked: bool,
+}
+
+/// One folder in the games root, returned by `list_dummy_games`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DummyGame {
+    pub app_id: String,
+    pub name: Option<String>,
+    pub path: PathBuf,
+    /// Bytes deleting the folder would free.
+    pub size: u64,
+    /// Bytes of runner copies sharing their data with the stored runner or
+    /// other folders, which deleting the folder leaves in place.
+    pub shared: u64,
+    /// Unix time in milliseconds of the last start, or of the last change to
+    /// the folder if it was never started.
+    pub last_used: Option<u64>,
+    /// Why `remove_dummy_games` would refuse this folder, if it would.
+    pub protected: Option<String>,
+}
+
+/// Outcome for one app id passed to `remove_dummy_games`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Removal {
+    pub app_id: String,
+    /// Bytes freed; `None` if the folder was kept.
+    pub freed: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Hex SHA-256 of a file's contents.
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+fn read_manifest(game_dir: &Path) -> Option<Manifest> {
+    let data = fs::read(game_dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+fn write_manifest(game_dir: &Path, manifest: &Manifest) -> io::Result<()> {
+    let json = serde_json::to_vec_pretty(manifest)?;
+    storage::write_atomic(&game_dir.join(MANIFEST_FILE), &json)
+}
+
+/// `path` relative to `base` with `/` separators, if it is inside it.
+fn relative(base: &Path, path: &Path) -> Option<String> {
+    let rel = path.strip_prefix(base).ok()?;
+    let parts: Vec<String> = rel.iter().map(|p| p.to_string_lossy().to_string()).collect();
+    Some(parts.join("/"))
+}
+
+/// Records a copy of `runner` placed at `file` by `method`, so it can be
+/// recognised later.
+pub fn record_install(
+    game_dir: &Path,
+    app_id: &str,
+    name: Option<&str>,
+    file: &Path,
+    runner: &Runner,
+    method: PlaceMethod,
+) -> io::Result<()> {
+    let path = relative(game_dir, file)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "runner copy is outside its game folder"))?;
+    let mut manifest = read_manifest(game_dir).unwrap_or_default();
+    let linked = match method {
+        PlaceMethod::Reflink | PlaceMethod::HardLink => true,
+        PlaceMethod::Copy => false,
+        // The file there was kept, and so is how it got there
+        PlaceMethod::Reused => manifest
+            .files
+            .iter()
+            .any(|f| f.path == path && f.sha256 == runner.sha256 && f.linked),
+    };
+    let entry = RunnerFile {
+        path,
+        size: runner.size,
+        sha256: runner.sha256.clone(),
+        linked,
+    };
+
+    manifest.app_id = app_id.to_string();
+    if let Some(name) = name {
+        manifest.name = Some(name.to_string());
+    }
+    manifest.files.retain(|f| f.path != entry.path);
+    manifest.files.push(entry);
+    write_manifest(game_dir, &manifest)
+}
+
+/// Notes that the game in `game_dir` was just started.
+pub fn record_use(game_dir: &Path, app_id: &str, name: &str) -> io::Result<()> {
+    let mut manifest = read_manifest(game_dir).unwrap_or_default();
+    manifest.app_id = app_id.to_string();
+    manifest.name = Some(name.to_string());
+    manifest.last_used = Some(now_millis());
+    write_manifest(game_dir, &manifest)
+}
+
+/// Whether the file at `rel`, of `size` bytes, is a runner copy: recorded in
+/// the manifest and unchanged since, identical to the bundled runner, or
+/// identical to a runner from before manifests were kept.
+fn known_copy(path: &Path, rel: &str, size: u64, manifest: Option<&Manifest>, runner_sha256: Option<&str>) -> bool {
+    let recorded = manifest.and_then(|m| m.files.iter().find(|f| f.path == rel));
+    match sha256_file(path) {
+        Ok(hash) => {
+            recorded.is_some_and(|f| f.size == size && f.sha256 == hash)
+                || runner_sha256 == Some(hash.as_str())
+                || runner_copy::PREVIOUS_SHA256.contains(&hash.as_str())
+        }
+        Err(_) => false,
+    }
+}
+
+/// Whether `file` in `game_dir` may be replaced by a new runner copy: it is
+/// missing, or it is one of our runner copies.
+pub fn is_replaceable(game_dir: &Path, file: &Path, runner_sha256: Option<&str>) -> bool {
+    let meta = match fs::symlink_metadata(file) {
+        Ok(meta) => meta,
+        Err(e) => return e.kind() == io::ErrorKind::NotFound,
+    };
+    if !meta.is_file() {
+        return false;
+    }
+    let Some(rel) = relative(game_dir, file) else {
+        return false;
+    };
+    known_copy(file, &rel, meta.len(), read_manifest(game_dir).as_ref(), runner_sha256)
+}
+
+/// Whether deleting a runner copy with `meta` leaves its data in place: it
+/// has other hard links, or it was linked to a stored copy that still exists.
+fn shares_data(meta: &fs::Metadata, recorded: Option<&RunnerFile>, store_dir: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if meta.nlink() > 1 {
+            return true;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = meta;
+    recorded.is_some_and(|f| f.linked && store_dir.join(&f.sha256).is_file())
+}
+
+/// What was found in a game folder.
+struct Inspection {
+    /// Bytes deleting the folder would free.
+    size: u64,
+    /// Bytes of runner copies whose data lives on elsewhere.
+    shared: u64,
+    newest_change: Option<u64>,
+    /// Why the folder must not be deleted, if it mustn't.
+    protected: Option<String>,
+}
+
+/// Whether `rel` is in the same folder as a runner copy in the manifest.
+fn beside_recorded(rel: &str, manifest: Option<&Manifest>) -> bool {
+    let folder = |path: &str| path.rsplit_once('/').map_or("", |(dir, _)| dir).to_string();
+    manifest.is_some_and(|m| m.files.iter().any(|f| folder(&f.path) == folder(rel)))
+}
+
+/// Keeps the first reason found.
+fn protect(inspection: &mut Inspection, reason: String) {
+    if inspection.protected.is_none() {
+        inspection.protected = Some(reason);
+    }
+}
+
+/// Walks a game folder. It may only be deleted if everything in it is the
+/// manifest, a folder, a runner's debug log beside a recorded runner copy,
+/// or a runner copy: a file recorded in the manifest that is unchanged, or
+/// one identical to the bundled runner. `store_dir` is where the runner copies are linked from.
+fn inspect(game_dir: &Path, store_dir: &Path, manifest: Option<&Manifest>, runner_sha256: Option<&str>) -> Inspection {
+    let mut inspection = Inspection {
+        size: 0,
+        shared: 0,
+        newest_change: None,
+        protected: None,
+    };
+    match fs::symlink_metadata(game_dir) {
+        Ok(meta) if meta.file_type().is_symlink() => protect(&mut inspection, "the folder is a link".to_string()),
+        Ok(meta) if !meta.is_dir() => protect(&mut inspection, "not a folder".to_string()),
+        Ok(_) => {}
+        Err(e) => {
+            protect(&mut inspection, e.to_string());
+            return inspection;
+        }
+    }
+
+    let mut pending = vec![game_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                protect(&mut inspection, format!("{:?} can't be read: {}", dir, e));
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let rel = relative(game_dir, &path).unwrap_or_default();
+            let Ok(meta) = fs::symlink_metadata(&path) else {
+                protect(&mut inspection, format!("{} can't be read", rel));
+                continue;
+            };
+            let changed = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64);
+            inspection.newest_change = inspection.newest_change.max(changed);
+
+            if meta.file_type().is_symlink() {
+                protect(&mut inspection, format!("{} is a link", rel));
+                continue;
+            }
+            if meta.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            if (dir == game_dir && rel == MANIFEST_FILE)
+                || (entry.file_name() == RUNNER_LOG_FILE && beside_recorded(&rel, manifest))
+            {
+                inspection.size += meta.len();
+                continue;
+            }
+
+            if !known_copy(&path, &rel, meta.len(), manifest, runner_sha256) {
+                protect(&mut inspection, format!("{} is not a runner copy", rel));
+                inspection.size += meta.len();
+                continue;
+            }
+            let recorded = manifest.and_then(|m| m.files.iter().find(|f| f.path == rel));
+            if shares_data(&meta, recorded, store_dir) {
+                inspection.shared += meta.len();
+            } else {
+                inspection.size += meta.len();
+            }
+        }
+    }
+    inspection
+}
+
+/// App ids whose folder is in use: a running session, or a queued game.
+fn in_use<R: Runtime>(handle: &AppHandle<R>) -> HashSet<String> {
+    let mut ids: HashSet<String> = handle
+        .state::<SessionRegistry>()
+        .list()
+        .into_iter()
+        .filter(|s| s.is_active())
+        .map(|s| s.app_id)
+        .collect();
+    let queue = handle.state::<QueueManager>().snapshot();
+    ids.extend(queue.current.map(|c| c.item.game.app_id));
+    ids.extend(queue.items.into_iter().map(|i| i.game.app_id));
+    ids
+}
+
+/// The game folders in `root`, with what is known about each.
+fn scan(root: &Path, runner_sha256: Option<&str>, in_use: &HashSet<String>) -> Vec<DummyGame> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut games: Vec<DummyGame> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let app_id = entry.file_name().to_str()?.to_string();
+            game_paths::validate_app_id(&app_id).ok()?;
+            let path = entry.path();
+            let manifest = read_manifest(&path);
+            let inspection = inspect(&path, &root.join(runner_copy::STORE_DIR), manifest.as_ref(), runner_sha256);
+            let protected = if in_use.contains(&app_id) {
+                Some("in use by a running or queued game".to_string())
+            } else {
+                inspection.protected
+            };
+            Some(DummyGame {
+                name: manifest.as_ref().and_then(|m| m.name.clone()),
+                last_used: manifest.as_ref().and_then(|m| m.last_used).or(inspection.newest_change),
+                app_id,
+                path,
+                size: inspection.size,
+                shared: inspection.shared,
+                protected,
+            })
+        })
+        .collect();
+    games.sort_by_key(|g| std::cmp::Reverse(g.last_used));
+    games
+}
+
+fn runner_sha256<R: Runtime>(handle: &AppHandle<R>) -> Option<String> {
+    runner_copy::bundled(handle).ok().map(|r| r.sha256)
+}
+
+/// Every game folder, most recently used first. Names missing from a folder
+/// are filled in from the catalog.
+pub fn list<R: Runtime>(handle: &AppHandle<R>) -> Result<Vec<DummyGame>, String> {
+    let root = game_paths::games_root(handle).map_err(|e| e.to_string())?;
+    let mut games = scan(&root.path, runner_sha256(handle).as_deref(), &in_use(handle));
+    let catalog = handle.state::<CatalogStore>();
+    for game in games.iter_mut().filter(|g| g.name.is_none()) {
+        game.name = catalog.game(&game.app_id).map(|g| g.name);
+    }
+    Ok(games)
+}
+
+/// Deletes the folders of `app_ids`, each only if it holds nothing but runner
+/// copies and isn't in use.
+pub fn remove<R: Runtime>(handle: &AppHandle<R>, app_ids: &[String]) -> Result<Vec<Removal>, String> {
+    let games = GamePaths::for_app(handle).map_err(|e| e.to_string())?;
+    let runner = runner_sha256(handle);
+    let busy = in_use(handle);
+    Ok(app_ids
+        .iter()
+        .map(|app_id| {
+            let result = remove_folder(&games, app_id, runner.as_deref(), &busy);
+            Removal {
+                app_id: app_id.clone(),
+                freed: result.as_ref().ok().copied(),
+                error: result.err(),
+            }
+        })
+        .collect())
+}
+
+/// Deletes the folder of `app_id` under `games`, unless it is in `in_use` or
+/// holds anything but runner copies. Returns the bytes freed, which leaves
+/// out runner copies whose data lives on elsewhere.
+pub fn remove_folder(
+    games: &GamePaths,
+    app_id: &str,
+    runner_sha256: Option<&str>,
+    in_use: &HashSet<String>,
+) -> Result<u64, String> {
+    let dir = games.game_dir(app_id).map_err(|e| e.to_string())?;
+    if in_use.contains(app_id) {
+        return Err("in use by a running or queued game".to_string());
+    }
+    let manifest = read_manifest(&dir);
+    let store_dir = games.root().join(runner_copy::STORE_DIR);
+    let inspection = inspect(&dir, &store_dir, manifest.as_ref(), runner_sha256);
+    if let Some(reason) = inspection.protected {
+        return Err(format!("Kept {:?}: {}", dir, reason));
+    }
+    fs::remove_dir_all(&dir).map_err(|e| format!("Failed to remove {:?}: {}", dir, e))?;
+    log::info!("Removed dummy game {:?} ({} bytes)", dir, inspection.size);
+    Ok(inspection.size)
+}
+
+/// Removes folders unused for longer than the retention setting. Folders that
+/// aren't plain runner copies are left alone.
+pub fn prune<R: Runtime>(handle: &AppHandle<R>) {
+    let retention = handle.state::<SettingsStore>().get().games_retention();
+    let Some(retention) = retention else {
+        return;
+    };
+    let games = match list(handle) {
+        Ok(games) => games,
+        Err(e) => {
+            log::warn!("Skipping dummy game cleanup: {}", e);
+            return;
+        }
+    };
+    let cutoff = now_millis().saturating_sub(retention.as_millis() as u64);
+    let stale: Vec<String> = games
+        .into_iter()
+        .filter(|g| g.protected.is_none() && g.last_used.is_some_and(|t| t < cutoff))
+        .map(|g| g.app_id)
+        .collect();
+    if stale.is_empty() {
+        return;
+    }
+    match remove(handle, &stale) {
+        Ok(removals) => {
+            for removal in removals.iter().filter(|r| r.error.is_some()) {
+                log::warn!("Kept unused dummy game {}: {}", removal.app_id, removal.error.as_deref().unwrap_or(""));
+            }
+        }
+        Err(e) => log::warn!("Dummy game cleanup failed: {}", e),
+    }
+}
+
+/// Replaces recorded runner copies from an earlier build with the bundled
+/// runner, so an update reaches every game folder. Copies changed since they
+/// were made, and folders in use, are left alone.
+pub fn refresh<R: Runtime>(handle: &AppHandle<R>) {
+    let runner = match runner_copy::bundled(handle) {
+        Ok(runner) => runner,
+        Err(e) => {
+            log::warn!("Skipping runner refresh: {}", e);
+            return;
+        }
+    };
+    let games = match GamePaths::for_app(handle) {
+        Ok(games) => games,
+        Err(e) => {
+            log::warn!("Skipping runner refresh: {}", e);
+            return;
+        }
+    };
+    let Ok(entries) = fs::read_dir(games.root()) else {
+        return;
+    };
+    let busy = in_use(handle);
+    for entry in entries.flatten() {
+        let Some(app_id) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if game_paths::validate_app_id(&app_id).is_err() || busy.contains(&app_id) {
+            continue;
+        }
+        let dir = entry.path();
+        let Some(manifest) = read_manifest(&dir).or_else(|| adopt_previous(&dir, &app_id)) else {
+            continue;
+        };
+        for file in manifest.files.iter().filter(|f| f.sha256 != runner.sha256) {
+            if let Err(e) = refresh_one(&games, &app_id, &dir, manifest.name.as_deref(), file, &runner) {
+                log::warn!("Failed to refresh runner {} in {:?}: {}", file.path, dir, e);
+            }
+        }
+    }
+    runner_copy::prune_store(games.root(), &runner.sha256);
+}
+
+/// Writes a manifest for a folder made before manifests were kept, listing
+/// the copies of earlier runners in it, so they are refreshed like any other.
+/// `None` if it holds none.
+fn adopt_previous(game_dir: &Path, app_id: &str) -> Option<Manifest> {
+    let mut files = Vec::new();
+    let mut pending = vec![game_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(meta) = fs::symlink_metadata(&path) else {
+                continue;
+            };
+            if meta.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let Some(rel) = relative(game_dir, &path) else {
+                continue;
+            };
+            match sha256_file(&path) {
+                Ok(hash) if meta.is_file() && runner_copy::PREVIOUS_SHA256.contains(&hash.as_str()) => {
+                    files.push(RunnerFile {
+                        path: rel,
+                        size: meta.len(),
+                        sha256: hash,
+                        linked: false,
+                    })
+                }
+                _ => {}
+            }
+        }
+    }
+    if files.is_empty() {
+        return None;
+    }
+
+    let manifest = Manifest {
+        app_id: app_id.to_string(),
+        files,
+        ..Manifest::default()
+    };
+    if let Err(e) = write_manifest(game_dir, &manifest) {
+        log::warn!("Failed to record earlier runner copies in {:?}: {}", game_dir, e);
+    }
+    log::info!("Found {} earlier runner copies in {:?}", manifest.files.len(), game_dir);
+    Some(manifest)
+}
+
+fn refresh_one(
+    games: &GamePaths,
+    app_id: &str,
+    game_dir: &Path,
+    name: Option<&str>,
+    file: &RunnerFile,
+    runner: &Runner,
+) -> Result<(), String> {
+    let target = games.prepare(app_id, "", &file.path).map_err(|e| e.to_string())?;
+    let unchanged = fs::metadata(&target).is_ok_and(|m| m.len() == file.size)
+        && sha256_file(&target).is_ok_and(|hash| hash == file.sha256);
+    if !unchanged {
+        return Ok(());
+    }
+    let method = runner_copy::place(runner, games.root(), &target).map_err(|e| e.to_string())?;
+    record_install(game_dir, app_id, name, &target, runner, method).map_err(|e| e.to_string())?;
+    log::info!("Refreshed runner {:?}", target);
+    Ok(())
+}
@@ -0,0 +1,337 @@
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::{DetectableApp, Executable};
+use crate::game_paths;
+
+/// An OS as named in the detectable list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HostOs {
+    Win32,
+    Darwin,
+    Linux,
+}
+
+impl HostOs {
+    /// The OS this build runs on. Anything unknown is treated as Linux, the
+    /// closest match for the other Unixes.
+    pub fn current() -> Self {
+        match std::env::consts::OS {
+            "windows" => HostOs::Win32,
+            "macos" => HostOs::Darwin,
+            _ => HostOs::Linux,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HostOs::Win32 => "win32",
+            HostOs::Darwin => "darwin",
+            HostOs::Linux => "linux",
+        }
+    }
+}
+
+/// A file name the runner can be copied to so Discord sees the game.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutableCandidate {
+    /// Relative path to create, with `/` between folders.
+    pub name: String,
+    /// The OS the catalog listed it for; `None` for a name made up from the game.
+    pub os: Option<String>,
+    /// Higher is better.
+    pub score: u32,
+    /// Why it ranks where it does, most important first.
+    pub reasons: Vec<String>,
+}
+
+/// A listed executable that can't be used.
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedExecutable {
+    pub os: String,
+    pub name: String,
+    pub reason: String,
+}
+
+/// Returned by `resolve_executable`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedExecutable {
+    pub app_id: String,
+    pub os: HostOs,
+    /// Best first. Never empty: a game without usable executables gets one
+    /// named after it.
+    pub candidates: Vec<ExecutableCandidate>,
+    pub skipped: Vec<SkippedExecutable>,
+}
+
+/// Ranks the executables of `game` for `os`, following how Discord reads the
+/// detectable list:
+/// - entries for `os` come first; on Linux, Windows entries follow, since
+///   Discord also sees games run under Wine or Proton;
+/// - launchers are skipped, as Discord doesn't count them as playing;
+/// - a leading `>` means the name must match exactly, and is not part of it;
+/// - folders in a name are part of what Discord matches, so they are kept;
+/// - entries that also expect particular arguments rank lower, as the runner
+///   is not started with them.
+pub fn resolve(game: &DetectableApp, os: HostOs) -> ResolvedExecutable {
+    let mut candidates: Vec<ExecutableCandidate> = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (position, exe) in game.executables.iter().enumerate() {
+        let skip = |reason: String| SkippedExecutable {
+            os: exe.os.clone(),
+            name: exe.name.clone(),
+            reason,
+        };
+        let Some(base) = os_score(&exe.os, os) else {
+            skipped.push(skip(format!("listed for {}, which Discord on {} doesn't match", exe.os, os.as_str())));
+            continue;
+        };
+        if exe.is_launcher {
+            skipped.push(skip("a launcher; Discord doesn't count it as playing".to_string()));
+            continue;
+        }
+        match candidate(exe, os, base, position) {
+            Ok(candidate) => {
+                if !candidates.iter().any(|c| c.name.eq_ignore_ascii_case(&candidate.name)) {
+                    candidates.push(candidate);
+                }
+            }
+            Err(reason) => skipped.push(skip(reason)),
+        }
+    }
+
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.score));
+    if candidates.is_empty() {
+        candidates.push(fallback(&game.name, os));
+    }
+
+    ResolvedExecutable {
+        app_id: game.id.clone(),
+        os,
+        candidates,
+        skipped,
+    }
+}
+
+/// How well an entry listed for `listed` suits `host`, if at all.
+fn os_score(listed: &str, host: HostOs) -> Option<u32> {
+    if listed == host.as_str() {
+        return Some(1000);
+    }
+    match (host, listed) {
+        (HostOs::Linux, "win32") => Some(500),
+        _ => None,
+    }
+}
+
+fn candidate(exe: &Executable, os: HostOs, base: u32, position: usize) -> Result<ExecutableCandidate, String> {
+    let mut reasons = Vec::new();
+    let mut score = base;
+    if exe.os == os.as_str() {
+        reasons.push(format!("listed for {}", exe.os));
+    } else {
+        reasons.push(format!(
+            "listed for {}; Discord on {} also matches it when run under Wine or Proton",
+            exe.os,
+            os.as_str()
+        ));
+    }
+
+    let mut name = exe.name.as_str();
+    if let Some(exact) = name.strip_prefix('>') {
+        name = exact;
+        reasons.push("'>' prefix: Discord matches this name exactly".to_string());
+    }
+
+    // The same checks creating the game applies, so every candidate can be made
+    let parts = game_paths::validate_executable_name(name).map_err(|e| e.to_string())?;
+    let Some(file) = parts.last() else {
+        return Err("empty file name".to_string());
+    };
+    if parts.len() > 1 {
+        reasons.push(format!("Discord matches the folder too, so it is created as {}", parts.join("/")));
+    }
+    if exe.os == "win32" && !file.to_lowercase().ends_with(".exe") {
+        score -= 100;
+        reasons.push("not an .exe".to_string());
+    }
+    if let Some(arguments) = &exe.arguments {
+        score -= 200;
+        reasons.push(format!("Discord also expects the arguments {:?}, which the runner isn't started with", arguments));
+    }
+    // Keep the list's own order among otherwise equal entries
+    score = score.saturating_sub(position.min(99) as u32);
+
+    Ok(ExecutableCandidate {
+        name: parts.join("/"),
+        os: Some(exe.os.clone()),
+        score,
+        reasons,
+    })
+}
+
+/// A file named after the game, for games that list nothing usable. Falls
+/// back to `game` when nothing usable is left of the name.
+fn fallback(game_name: &str, os: HostOs) -> ExecutableCandidate {
+    let stem: String = game_name
+        .chars()
+        .filter(|c| !game_paths::forbidden_in_names(*c) && !matches!(c, '/' | '\\'))
+        .collect();
+    let with_extension = |stem: &str| match os {
+        HostOs::Win32 => format!("{}.exe", stem),
+        HostOs::Darwin | HostOs::Linux => stem.to_string(),
+    };
+    let stem = stem.trim_start().trim_end_matches(['.', ' ']);
+    let name = with_extension(stem);
+    let name = if !stem.is_empty() && game_paths::validate_executable_name(&name).is_ok() {
+        name
+    } else {
+        with_extension("game")
+    };
+    ExecutableCandidate {
+        name,
+        os: None,
+        score: 0,
+        reasons: vec![format!("no usable executable listed for {}; named after the game", os.as_str())],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exe(os: &str, name: &str) -> Executable {
+        Executable {
+            os: os.to_string(),
+            name: name.to_string(),
+            is_launcher: false,
+            arguments: None,
+        }
+    }
+
+    fn game(name: &str, executables: Vec<Executable>) -> DetectableApp {
+        DetectableApp {
+            id: "1158877933042143272".to_string(),
+            name: name.to_string(),
+            aliases: Vec::new(),
+            icon: None,
+            splash: None,
+            executables,
+        }
+    }
+
+    fn names(resolved: &ResolvedExecutable) -> Vec<&str> {
+        resolved.candidates.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    #[test]
+    fn skips_launchers() {
+        let launcher = Executable {
+            is_launcher: true,
+            ..exe("win32", "launcher.exe")
+        };
+        let resolved = resolve(&game("Game", vec![launcher, exe("win32", "game.exe")]), HostOs::Win32);
+        assert_eq!(names(&resolved), ["game.exe"]);
+        assert_eq!(resolved.skipped.len(), 1);
+        assert_eq!(resolved.skipped[0].name, "launcher.exe");
+    }
+
+    #[test]
+    fn strips_the_exact_match_prefix() {
+        let resolved = resolve(&game("Game", vec![exe("win32", ">bin/win64/game.exe")]), HostOs::Win32);
+        assert_eq!(names(&resolved), ["bin/win64/game.exe"]);
+    }
+
+    #[test]
+    fn prefers_native_entries_on_linux() {
+        let entries = vec![exe("win32", "game.exe"), exe("darwin", "game.app"), exe("linux", "game.x86_64")];
+        let resolved = resolve(&game("Game", entries.clone()), HostOs::Linux);
+        // Windows entries still count under Wine or Proton; macOS ones don't
+        assert_eq!(names(&resolved), ["game.x86_64", "game.exe"]);
+        assert_eq!(resolved.skipped.len(), 1);
+
+        let resolved = resolve(&game("Game", entries), HostOs::Win32);
+        assert_eq!(names(&resolved), ["game.exe"]);
+    }
+
+    #[test]
+    fn ranks_entries_with_arguments_lower() {
+        let with_arguments = Executable {
+            arguments: Some("--game".to_string()),
+            ..exe("win32", "launcher-game.exe")
+        };
+        let resolved = resolve(&game("Game", vec![with_arguments, exe("win32", "game.exe")]), HostOs::Win32);
+        assert_eq!(names(&resolved), ["game.exe", "launcher-game.exe"]);
+        assert_eq!(resolved.candidates[1].score, 1000 - 200);
+    }
+
+    #[test]
+    fn ranks_non_exe_windows_entries_lower() {
+        let resolved = resolve(&game("Game", vec![exe("win32", "game.bat"), exe("win32", "game.exe")]), HostOs::Win32);
+        assert_eq!(names(&resolved), ["game.exe", "game.bat"]);
+    }
+
+    #[test]
+    fn keeps_list_order_among_equals() {
+        let entries = vec![exe("win32", "b.exe"), exe("win32", "a.exe"), exe("win32", "B.EXE")];
+        let resolved = resolve(&game("Game", entries), HostOs::Win32);
+        assert_eq!(names(&resolved), ["b.exe", "a.exe"]);
+    }
+
+    #[test]
+    fn refuses_paths_leaving_the_folder() {
+        let entries = vec![exe("win32", "../game.exe"), exe("win32", "/game.exe")];
+        let resolved = resolve(&game("Game", entries), HostOs::Win32);
+        assert_eq!(resolved.skipped.len(), 2);
+        assert_eq!(resolved.candidates[0].os, None);
+    }
+
+    #[test]
+    fn falls_back_to_the_game_name() {
+        let resolved = resolve(&game("Stardew Valley", Vec::new()), HostOs::Win32);
+        assert_eq!(names(&resolved), ["Stardew Valley.exe"]);
+        assert_eq!(resolved.candidates[0].os, None);
+
+        let resolved = resolve(&game("Stardew Valley", vec![exe("darwin", "Stardew Valley.app")]), HostOs::Linux);
+        assert_eq!(names(&resolved), ["Stardew Valley"]);
+
+        let resolved = resolve(&game("AC/DC: Live?", Vec::new()), HostOs::Win32);
+        assert_eq!(names(&resolved), ["ACDC Live.exe"]);
+
+        let resolved = resolve(&game("...", Vec::new()), HostOs::Linux);
+        assert_eq!(names(&resolved), ["game"]);
+        let resolved = resolve(&game("Con", Vec::new()), HostOs::Win32);
+        assert_eq!(names(&resolved), ["game.exe"]);
+    }
+
+    #[test]
+    fn candidates_can_always_be_created() {
+        // Names that are fine on Linux but not on Windows are refused on
+        // every OS, so the fallback must avoid them too
+        let resolved = resolve(&game("Halo: Reach", Vec::new()), HostOs::Linux);
+        assert_eq!(names(&resolved), ["Halo Reach"]);
+        let resolved = resolve(&game("What? Remastered.", Vec::new()), HostOs::Darwin);
+        assert_eq!(names(&resolved), ["What Remastered"]);
+
+        let entries = vec![exe("linux", "bin/game:server"), exe("linux", "CON"), exe("linux", "bin/game")];
+        let resolved = resolve(&game("Halo: Reach", entries), HostOs::Linux);
+        assert_eq!(names(&resolved), ["bin/game"]);
+        assert_eq!(resolved.skipped.len(), 2);
+
+        for name in ["Halo: Reach", "a|b", "*", "trailing. ", " lpt1", "\\server\\share"] {
+            for os in [HostOs::Win32, HostOs::Darwin, HostOs::Linux] {
+                let resolved = resolve(&game(name, vec![exe(os.as_str(), name)]), os);
+                for candidate in &resolved.candidates {
+                    assert!(
+                        game_paths::validate_executable_name(&candidate.name).is_ok(),
+                        "{:?} on {:?} gave {:?}",
+                        name,
+                        os,
+                        candidate.name
+                    );
+                }
+            }
+        }
+    }
+}
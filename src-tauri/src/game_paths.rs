@@ -0,0 +1,501 @@
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::settings::SettingsStore;
+use crate::storage;
+
+/// Longest file or folder name accepted, the common filesystem limit.
+const MAX_COMPONENT_LEN: usize = 255;
+
+/// Why a game folder or executable path was refused.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "detail", rename_all = "snake_case")]
+pub enum PathError {
+    /// App ids are Discord snowflakes: decimal digits that fit in a u64.
+    InvalidAppId(String),
+    /// A required value was empty.
+    Empty { field: &'static str },
+    /// Rooted, or carrying a drive or UNC prefix.
+    Absolute { field: &'static str, value: String },
+    /// Contains a `..` component.
+    ParentComponent { field: &'static str, value: String },
+    /// A component that can't be used as a file name on every platform.
+    InvalidComponent {
+        field: &'static str,
+        component: String,
+        reason: &'static str,
+    },
+    /// Resolves outside the games folder, e.g. through a symbolic link.
+    EscapesRoot { path: PathBuf },
+    Io { path: PathBuf, message: String },
+    /// Neither the settings, portable mode nor the platform give a games folder.
+    NoGamesRoot,
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::InvalidAppId(id) => write!(f, "{:?} is not a valid app id", id),
+            PathError::Empty { field } => write!(f, "{} is empty", field),
+            PathError::Absolute { field, value } => write!(f, "{} {:?} must be a relative path", field, value),
+            PathError::ParentComponent { field, value } => {
+                write!(f, "{} {:?} must not contain '..'", field, value)
+            }
+            PathError::InvalidComponent {
+                field,
+                component,
+                reason,
+            } => write!(f, "{} contains {:?}, which {}", field, component, reason),
+            PathError::EscapesRoot { path } => write!(f, "{:?} is outside the games folder", path),
+            PathError::Io { path, message } => write!(f, "{:?}: {}", path, message),
+            PathError::NoGamesRoot => f.write_str("No folder is available for dummy games"),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+/// Checks that `id` is a snowflake and returns it unchanged.
+pub fn validate_app_id(id: &str) -> Result<&str, PathError> {
+    let valid = !id.is_empty()
+        && id.len() <= 20
+        && id.bytes().all(|b| b.is_ascii_digit())
+        && !(id.len() > 1 && id.starts_with('0'))
+        && id.parse::<u64>().is_ok();
+    if valid {
+        Ok(id)
+    } else {
+        Err(PathError::InvalidAppId(id.to_string()))
+    }
+}
+
+/// Splits a caller-supplied relative path on `/` and `\` into components that
+/// are safe on every platform. Empty and `.` components are dropped.
+fn relative_components<'a>(field: &'static str, value: &'a str) -> Result<Vec<&'a str>, PathError> {
+    let rooted = value.starts_with(['/', '\\']);
+    let prefixed = value.len() >= 2 && value.as_bytes()[1] == b':' && value.as_bytes()[0].is_ascii_alphabetic();
+    if rooted || prefixed || Path::new(value).is_absolute() {
+        return Err(PathError::Absolute {
+            field,
+            value: value.to_string(),
+        });
+    }
+
+    let mut components = Vec::new();
+    for component in value.split(['/', '\\']) {
+        match component {
+            "" | "." => continue,
+            ".." => {
+                return Err(PathError::ParentComponent {
+                    field,
+                    value: value.to_string(),
+                })
+            }
+            _ => {}
+        }
+        if let Some(reason) = invalid_reason(component) {
+            return Err(PathError::InvalidComponent {
+                field,
+                component: component.to_string(),
+                reason,
+            });
+        }
+        components.push(component);
+    }
+    Ok(components)
+}
+
+/// Splits an executable name like `relative_components` does, and fails if
+/// nothing is left of it.
+pub fn validate_executable_name(value: &str) -> Result<Vec<&str>, PathError> {
+    let components = relative_components("executable_name", value)?;
+    if components.is_empty() {
+        return Err(PathError::Empty {
+            field: "executable_name",
+        });
+    }
+    Ok(components)
+}
+
+/// Characters refused in file and folder names, which Windows doesn't allow.
+/// `/` and `\` separate components instead.
+pub fn forbidden_in_names(c: char) -> bool {
+    c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*')
+}
+
+fn invalid_reason(component: &str) -> Option<&'static str> {
+    if component.len() > MAX_COMPONENT_LEN {
+        return Some("is too long");
+    }
+    if component.chars().any(|c| c.is_control()) {
+        return Some("contains a control character");
+    }
+    if component.chars().any(forbidden_in_names) {
+        return Some("contains a character Windows doesn't allow in file names");
+    }
+    // Windows drops these, so "..." or "bin. " would name some other folder
+    if component.ends_with(['.', ' ']) {
+        return Some("ends with a dot or a space");
+    }
+    let stem = component.split('.').next().unwrap_or(component).trim_end();
+    let numbered = |prefix: &str| {
+        stem.len() == 4
+            && stem.get(..3).is_some_and(|p| p.eq_ignore_ascii_case(prefix))
+            && matches!(stem.as_bytes()[3], b'1'..=b'9')
+    };
+    let reserved = ["CON", "PRN", "AUX", "NUL"].iter().any(|r| stem.eq_ignore_ascii_case(r))
+        || numbered("COM")
+        || numbered("LPT");
+    if reserved {
+        return Some("is a reserved device name on Windows");
+    }
+    None
+}
+
+/// Where the games folder came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GamesRootSource {
+    /// `games_root` in the settings.
+    Settings,
+    /// `games/` next to the executable, as the portable marker is there.
+    Portable,
+    /// `games/` in the platform's app data directory.
+    DataDir,
+}
+
+/// Returned by `get_games_root`.
+#[derive(Debug, Clone, Serialize)]
+pub struct GamesRoot {
+    pub path: PathBuf,
+    pub source: GamesRootSource,
+}
+
+/// The games folder: the settings override if there is one, else `games/`
+/// beside the executable in portable mode, else `games/` in the platform data
+/// directory (`~/.local/share/<app>` or `$XDG_DATA_HOME` on Linux).
+pub fn games_root<R: Runtime>(handle: &AppHandle<R>) -> Result<GamesRoot, PathError> {
+    let configured = handle.state::<SettingsStore>().get().games_root;
+    if let Some(path) = configured.filter(|p| !p.as_os_str().is_empty()) {
+        let path = match storage::data_dir(handle) {
+            Some(data) if path.is_relative() => data.join(path),
+            _ => path,
+        };
+        return Ok(GamesRoot {
+            path,
+            source: GamesRootSource::Settings,
+        });
+    }
+    if storage::is_portable() {
+        if let Some(dir) = storage::exe_dir() {
+            return Ok(GamesRoot {
+                path: dir.join("games"),
+                source: GamesRootSource::Portable,
+            });
+        }
+    }
+    let data = storage::data_dir(handle).ok_or(PathError::NoGamesRoot)?;
+    Ok(GamesRoot {
+        path: data.join("games"),
+        source: GamesRootSource::DataDir,
+    })
+}
+
+/// Builds paths inside the games folder from caller-supplied values. Each
+/// value is validated on its own, and paths that exist on disk are checked
+/// again after resolving links, so nothing outside the folder is ever
+/// created, overwritten or started.
+pub struct GamePaths {
+    root: PathBuf,
+}
+
+impl GamePaths {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        GamePaths { root: root.into() }
+    }
+
+    /// Paths under the configured games folder; see `games_root`.
+    pub fn for_app<R: Runtime>(handle: &AppHandle<R>) -> Result<Self, PathError> {
+        games_root(handle).map(|root| Self::new(root.path))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// `<root>/<app_id>`.
+    pub fn game_dir(&self, app_id: &str) -> Result<PathBuf, PathError> {
+        Ok(self.root.join(validate_app_id(app_id)?))
+    }
+
+    /// `<root>/<app_id>/<path>`, the folder a game's runner starts in.
+    pub fn folder(&self, app_id: &str, path: &str) -> Result<PathBuf, PathError> {
+        let mut folder = self.game_dir(app_id)?;
+        folder.extend(relative_components("path", path)?);
+        Ok(folder)
+    }
+
+    /// `<root>/<app_id>/<path>/<executable_name>`, checked without touching
+    /// the disk. `path` may be empty; `executable_name` may hold folders.
+    pub fn executable(&self, app_id: &str, path: &str, executable_name: &str) -> Result<PathBuf, PathError> {
+        let mut target = self.folder(app_id, path)?;
+        target.extend(validate_executable_name(executable_name)?);
+        Ok(target)
+    }
+
+    /// Like `executable`, and creates the folders leading to it. Fails if any
+    /// of them turns out to lead outside the root, or if the target itself
+    /// is a link.
+    pub fn prepare(&self, app_id: &str, path: &str, executable_name: &str) -> Result<PathBuf, PathError> {
+        let target = self.executable(app_id, path, executable_name)?;
+        let parent = target.parent().unwrap_or(&self.root);
+        fs::create_dir_all(&self.root).map_err(|e| io_error(&self.root, e))?;
+        // Check what already exists first, so a linked folder can't get
+        // anything created through it
+        if let Some(existing) = parent.ancestors().find(|p| p.exists()) {
+            self.check_inside(existing)?;
+        }
+        fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+        self.check_inside(parent)?;
+        if fs::symlink_metadata(&target).is_ok_and(|m| m.file_type().is_symlink()) {
+            return Err(PathError::EscapesRoot { path: target });
+        }
+        Ok(target)
+    }
+
+    /// Like `executable`, for a file that must already exist. Returns the
+    /// resolved path that was checked against the root, so it is the one used.
+    pub fn existing(&self, app_id: &str, path: &str, executable_name: &str) -> Result<PathBuf, PathError> {
+        let target = self.executable(app_id, path, executable_name)?;
+        self.check_inside(&target)
+    }
+
+    /// Resolves links in `path` and fails unless it stays under the root.
+    fn check_inside(&self, path: &Path) -> Result<PathBuf, PathError> {
+        let root = self.root.canonicalize().map_err(|e| io_error(&self.root, e))?;
+        let resolved = path.canonicalize().map_err(|e| io_error(path, e))?;
+        if resolved.starts_with(&root) {
+            Ok(resolved)
+        } else {
+            Err(PathError::EscapesRoot { path: resolved })
+        }
+    }
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> PathError {
+    PathError::Io {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const APP_ID: &str = "1158877933042143272";
+
+    /// A fresh, empty directory for one test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("qp-game-paths-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn accepts_snowflake_ids() {
+        for id in [APP_ID, "0", "18446744073709551615"] {
+            assert_eq!(validate_app_id(id), Ok(id));
+        }
+    }
+
+    #[test]
+    fn rejects_hostile_app_ids() {
+        for id in [
+            "",
+            "..",
+            "../1158877933042143272",
+            "1158877933042143272/..",
+            "/etc",
+            "C:\\Windows",
+            "12a",
+            " 123",
+            "0123",
+            "18446744073709551616",
+            "123456789012345678901",
+            "１２３",
+        ] {
+            assert_eq!(validate_app_id(id), Err(PathError::InvalidAppId(id.to_string())), "{:?}", id);
+        }
+    }
+
+    #[test]
+    fn splits_relative_paths() {
+        assert_eq!(relative_components("path", "bin").unwrap(), ["bin"]);
+        assert_eq!(relative_components("path", "").unwrap(), Vec::<&str>::new());
+        assert_eq!(relative_components("path", "./bin//win64/").unwrap(), ["bin", "win64"]);
+        assert_eq!(relative_components("path", "win64\\game.exe").unwrap(), ["win64", "game.exe"]);
+    }
+
+    #[test]
+    fn rejects_absolute_paths() {
+        for value in ["/etc/passwd", "\\Windows", "C:\\Windows\\evil.exe", "c:evil.exe", "\\\\server\\share\\evil.exe"] {
+            assert!(
+                matches!(relative_components("path", value), Err(PathError::Absolute { .. })),
+                "{:?}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_parent_components() {
+        for value in ["..", "../evil.exe", "bin/../../evil.exe", "bin\\..\\..\\evil.exe", "a/./../b"] {
+            assert!(
+                matches!(relative_components("path", value), Err(PathError::ParentComponent { .. })),
+                "{:?}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unportable_components() {
+        for value in [
+            "evil\0.exe",
+            "line\nbreak.exe",
+            "what?.exe",
+            "pipe|.exe",
+            "stream.exe:hidden",
+            "...",
+            "bin. ",
+            "trailing.",
+            "CON",
+            "nul.txt",
+            "com1.exe",
+            "LPT9",
+        ] {
+            assert!(
+                matches!(relative_components("executable_name", value), Err(PathError::InvalidComponent { .. })),
+                "{:?}",
+                value
+            );
+        }
+        let long = "a".repeat(256);
+        assert!(matches!(
+            relative_components("executable_name", &long),
+            Err(PathError::InvalidComponent { .. })
+        ));
+        // Names that only look like device names are fine
+        assert!(relative_components("executable_name", "console.exe").is_ok());
+        assert!(relative_components("executable_name", "com10.exe").is_ok());
+    }
+
+    #[test]
+    fn builds_paths_under_the_root() {
+        let games = GamePaths::new("/games");
+        assert_eq!(
+            games.executable(APP_ID, "bin", "win64/game.exe").unwrap(),
+            PathBuf::from("/games").join(APP_ID).join("bin").join("win64").join("game.exe")
+        );
+        assert_eq!(
+            games.executable(APP_ID, "", "game.exe").unwrap(),
+            PathBuf::from("/games").join(APP_ID).join("game.exe")
+        );
+    }
+
+    #[test]
+    fn every_field_is_checked() {
+        let games = GamePaths::new("/games");
+        assert!(matches!(games.executable("../..", "bin", "game.exe"), Err(PathError::InvalidAppId(_))));
+        assert!(matches!(
+            games.executable(APP_ID, "../../..", "game.exe"),
+            Err(PathError::ParentComponent { field: "path", .. })
+        ));
+        assert!(matches!(
+            games.executable(APP_ID, "bin", "/usr/bin/evil"),
+            Err(PathError::Absolute { field: "executable_name", .. })
+        ));
+        assert!(matches!(
+            games.executable(APP_ID, "bin", ""),
+            Err(PathError::Empty { field: "executable_name" })
+        ));
+        assert!(matches!(
+            games.executable(APP_ID, "bin", "./"),
+            Err(PathError::Empty { field: "executable_name" })
+        ));
+    }
+
+    #[test]
+    fn prepare_creates_folders_inside_the_root() {
+        let root = scratch("prepare");
+        let games = GamePaths::new(root.join("games"));
+        let target = games.prepare(APP_ID, "bin", "win64/game.exe").unwrap();
+        assert!(target.parent().unwrap().is_dir());
+        assert!(target.starts_with(root.join("games")));
+        assert!(!target.exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn existing_requires_the_file() {
+        let root = scratch("existing");
+        let games = GamePaths::new(&root);
+        assert!(matches!(games.existing(APP_ID, "bin", "game.exe"), Err(PathError::Io { .. })));
+
+        let target = games.prepare(APP_ID, "bin", "game.exe").unwrap();
+        fs::write(&target, b"runner").unwrap();
+        let found = games.existing(APP_ID, "bin", "game.exe").unwrap();
+        assert_eq!(found, target.canonicalize().unwrap());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_folders_linked_outside_the_root() {
+        use std::os::unix::fs::symlink;
+
+        let root = scratch("linked-folder");
+        let outside = root.join("outside");
+        let games_root = root.join("games");
+        fs::create_dir_all(&outside).unwrap();
+        fs::create_dir_all(&games_root).unwrap();
+        symlink(&outside, games_root.join(APP_ID)).unwrap();
+
+        let games = GamePaths::new(&games_root);
+        assert!(matches!(
+            games.prepare(APP_ID, "bin", "game.exe"),
+            Err(PathError::EscapesRoot { .. })
+        ));
+        // Nothing was created through the link
+        assert!(!outside.join("bin").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_executables_linked_outside_the_root() {
+        use std::os::unix::fs::symlink;
+
+        let root = scratch("linked-file");
+        let outside = root.join("outside.bin");
+        fs::write(&outside, b"not a runner").unwrap();
+        let games = GamePaths::new(root.join("games"));
+        let target = games.prepare(APP_ID, "bin", "game.exe").unwrap();
+        symlink(&outside, &target).unwrap();
+
+        assert!(matches!(
+            games.prepare(APP_ID, "bin", "game.exe"),
+            Err(PathError::EscapesRoot { .. })
+        ));
+        assert!(matches!(
+            games.existing(APP_ID, "bin", "game.exe"),
+            Err(PathError::EscapesRoot { .. })
+        ));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,226 @@
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Certificate, Client, Proxy, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime};
+use tokio::time::Instant;
+
+/// First retry delay; doubled on every further attempt.
+const BASE_RETRY_DELAY: Duration = Duration::from_millis(500);
+/// A server asking to wait longer than this is not retried; backoff stops
+/// growing here.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Network settings for catalog downloads, part of the backend settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpSettings {
+    pub connect_timeout_secs: u64,
+    /// Longest gap allowed between two reads of a response.
+    pub read_timeout_secs: u64,
+    /// Whole refresh, all sources together.
+    pub fetch_deadline_secs: u64,
+    /// Extra attempts after a connection error, a 5xx or a 429.
+    pub max_retries: u32,
+    /// e.g. `http://proxy.internal:3128` or `socks5://127.0.0.1:1080`.
+    pub proxy: Option<String>,
+    /// PEM file with extra root certificates, for mirrors behind a private CA.
+    pub ca_certificate: Option<PathBuf>,
+    pub user_agent: String,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        HttpSettings {
+            connect_timeout_secs: 10,
+            read_timeout_secs: 30,
+            fetch_deadline_secs: 60,
+            max_retries: 3,
+            proxy: None,
+            ca_certificate: None,
+            user_agent: format!("QuestPasser/{}", env!("CARGO_PKG_VERSION")),
+        }
+    }
+}
+
+impl HttpSettings {
+    pub fn build_client(&self) -> Result<Client, String> {
+        let mut builder = Client::builder()
+            .connect_timeout(Duration::from_secs(self.connect_timeout_secs))
+            .read_timeout(Duration::from_secs(self.read_timeout_secs))
+            .user_agent(self.user_agent.as_str());
+
+        if let Some(proxy) = self.proxy.as_deref().filter(|p| !p.trim().is_empty()) {
+            let proxy = Proxy::all(proxy.trim()).map_err(|e| format!("Invalid proxy {:?}: {}", proxy, e))?;
+            builder = builder.proxy(proxy);
+        }
+        if let Some(path) = &self.ca_certificate {
+            let pem = std::fs::read(path)
+                .map_err(|e| format!("Failed to read CA certificate {:?}: {}", path, e))?;
+            let certs = Certificate::from_pem_bundle(&pem)
+                .map_err(|e| format!("Invalid CA certificate {:?}: {}", path, e))?;
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+
+        builder
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))
+    }
+}
+
+/// The HTTP client shared by every download, exposed as Tauri state and
+/// rebuilt whenever the network settings change.
+pub struct HttpClient {
+    client: RwLock<Client>,
+}
+
+impl HttpClient {
+    /// Builds the client from `settings`, falling back to the defaults if they
+    /// are invalid so the catalog can still load.
+    pub fn new(settings: &HttpSettings) -> Self {
+        let client = settings.build_client().unwrap_or_else(|e| {
+            log::warn!("{}; using default network settings", e);
+            HttpSettings::default()
+                .build_client()
+                .unwrap_or_else(|_| Client::new())
+        });
+        HttpClient {
+            client: RwLock::new(client),
+        }
+    }
+
+    pub fn get(&self) -> Client {
+        match self.client.read() {
+            Ok(client) => client.clone(),
+            Err(e) => e.into_inner().clone(),
+        }
+    }
+
+    pub fn configure(&self, settings: &HttpSettings) -> Result<(), String> {
+        let client = settings.build_client()?;
+        if let Ok(mut current) = self.client.write() {
+            *current = client;
+        }
+        Ok(())
+    }
+}
+
+/// How hard a request is retried, and until when.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub deadline: Instant,
+}
+
+/// Sends `request`, retrying connection errors, timeouts, 5xx and 429 with
+/// exponential backoff. A `Retry-After` is honoured; a retry that would end
+/// past the deadline is not attempted and the last outcome is returned.
+pub async fn send_with_retry(request: RequestBuilder, policy: RetryPolicy) -> reqwest::Result<Response> {
+    let mut attempt = 0;
+    loop {
+        // Requests with streaming bodies can't be replayed; ours never have one
+        let Some(this_try) = request.try_clone() else {
+            return request.send().await;
+        };
+        let outcome = this_try.send().await;
+
+        let retry_after = match &outcome {
+            Ok(res) if is_retryable(res.status()) => retry_after(res),
+            Err(e) if e.is_connect() || e.is_timeout() => None,
+            _ => return outcome,
+        };
+        let remaining = policy.deadline.saturating_duration_since(Instant::now());
+        let Some(delay) = retry_delay(attempt, policy.max_retries, retry_after, remaining) else {
+            return outcome;
+        };
+
+        attempt += 1;
+        match &outcome {
+            Ok(res) => log::info!("Retrying {} after {}: attempt {} in {:?}", res.url(), res.status(), attempt, delay),
+            Err(e) => log::info!("Retrying after {}: attempt {} in {:?}", e, attempt, delay),
+        }
+        tokio::time::sleep(delay).await;
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// How long to wait before retrying after `attempt` failed, or `None` to
+/// give up: out of retries, asked to wait longer than `MAX_RETRY_DELAY`, or
+/// the wait would not end within `remaining`.
+fn retry_delay(attempt: u32, max_retries: u32, retry_after: Option<Duration>, remaining: Duration) -> Option<Duration> {
+    if attempt >= max_retries {
+        return None;
+    }
+    let delay = retry_after.unwrap_or_else(|| backoff(attempt));
+    (delay <= MAX_RETRY_DELAY && delay < remaining).then_some(delay)
+}
+
+fn backoff(attempt: u32) -> Duration {
+    (BASE_RETRY_DELAY * 2u32.saturating_pow(attempt.min(6))).min(MAX_RETRY_DELAY)
+}
+
+fn retry_after(res: &Response) -> Option<Duration> {
+    parse_retry_after(res.headers().get(RETRY_AFTER)?.to_str().ok()?, SystemTime::now())
+}
+
+/// `Retry-After` given as delta-seconds or as an HTTP-date, which is
+/// measured from `now`. A date already past means retrying right away.
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLENTY: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let delays: Vec<Duration> = (0..4).map(|a| retry_delay(a, 10, None, PLENTY).unwrap()).collect();
+        assert_eq!(delays, [500, 1000, 2000, 4000].map(Duration::from_millis));
+        assert_eq!(retry_delay(6, 10, None, PLENTY), Some(MAX_RETRY_DELAY));
+        assert_eq!(retry_delay(9, 10, None, PLENTY), Some(MAX_RETRY_DELAY));
+        assert_eq!(retry_delay(10, 10, None, PLENTY), None);
+    }
+
+    #[test]
+    fn retry_after_replaces_backoff_unless_too_long() {
+        let asked = Some(Duration::from_secs(7));
+        assert_eq!(retry_delay(0, 3, asked, PLENTY), asked);
+        assert_eq!(retry_delay(0, 3, Some(MAX_RETRY_DELAY), PLENTY), Some(MAX_RETRY_DELAY));
+        assert_eq!(retry_delay(0, 3, Some(MAX_RETRY_DELAY + Duration::from_secs(1)), PLENTY), None);
+    }
+
+    #[test]
+    fn retries_must_end_before_the_deadline() {
+        assert_eq!(retry_delay(0, 3, None, Duration::from_millis(501)), Some(BASE_RETRY_DELAY));
+        assert_eq!(retry_delay(0, 3, None, BASE_RETRY_DELAY), None);
+        assert_eq!(retry_delay(0, 3, Some(Duration::from_secs(5)), Duration::from_secs(2)), None);
+        assert_eq!(retry_delay(0, 3, None, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn retry_after_takes_seconds_or_a_date() {
+        let now = httpdate::parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+        assert_eq!(parse_retry_after(" 120 ", now), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:50:07 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(parse_retry_after("Sun, 06 Nov 1994 08:00:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+        assert_eq!(parse_retry_after("-5", now), None);
+    }
+}
@@ -1,15 +1,56 @@
 use tauri::Manager;
 
+pub mod catalog;
+pub mod catalog_cache;
 pub mod commands;
+pub mod control;
+pub mod diagnostics;
+pub mod dummy_games;
+pub mod executables;
+pub mod game_paths;
+pub mod http;
+pub mod process;
+pub mod queue;
+pub mod recovery;
+pub mod runner_copy;
+pub mod runner_status;
+pub mod search;
+pub mod sessions;
+pub mod settings;
+pub mod sources;
+pub mod storage;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
     .plugin(tauri_plugin_log::Builder::default().build())
+    .manage(sessions::SessionRegistry::default())
+    .manage(queue::QueueManager::default())
+    .manage(catalog::CatalogStore::default())
     .setup(|app| {
+      let settings = settings::SettingsStore::load(app.handle());
+      app.manage(http::HttpClient::new(&settings.get().http));
+      app.manage(settings);
+
+      // Pick up a queue and runners left behind by a crash or an early exit,
+      // then clear out game folders that have gone unused and bring the
+      // runner copies in the rest up to date
+      let handle = app.handle().clone();
+      let saved = recovery::load(&handle);
+      tauri::async_runtime::spawn(async move {
+        if let Some(saved) = saved {
+          recovery::recover(handle.clone(), saved).await;
+        }
+        // Both walk and hash whole folders, so keep them off the async workers
+        let _ = tauri::async_runtime::spawn_blocking(move || {
+          dummy_games::prune(&handle);
+          dummy_games::refresh(&handle);
+        })
+        .await;
+      });
       #[cfg(debug_assertions)]
       {
-        let window = app.get_webview_window("main").unwrap();
+        let _window = app.get_webview_window("main").unwrap();
         // window.open_devtools(); 
       }
       Ok(())
@@ -18,7 +59,34 @@ pub fn run() {
         commands::create_dummy_game,
         commands::start_game_process,
         commands::stop_process,
-        commands::fetch_game_list
+        commands::get_runner_status,
+        commands::set_game_title,
+        commands::extend_game_duration,
+        commands::list_sessions,
+        commands::get_session,
+        commands::get_queue,
+        commands::queue_enqueue,
+        commands::queue_remove,
+        commands::queue_reorder,
+        commands::queue_clear,
+        commands::queue_start,
+        commands::queue_pause,
+        commands::queue_resume,
+        commands::queue_skip,
+        commands::queue_set_item_duration,
+        commands::get_settings,
+        commands::update_settings,
+        commands::fetch_game_list,
+        commands::get_catalog_page,
+        commands::get_catalog_counts,
+        commands::get_game,
+        commands::get_game_details,
+        commands::get_fetch_report,
+        commands::search_games,
+        commands::resolve_executable,
+        commands::get_games_root,
+        commands::list_dummy_games,
+        commands::remove_dummy_games
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
@@ -0,0 +1,255 @@
+use serde::Serialize;
+use std::fmt;
+use std::io;
+use std::process::{Child, ExitStatus};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a runner gets to exit on its own before it is killed.
+pub const GRACE_PERIOD: Duration = Duration::from_secs(3);
+const KILL_TIMEOUT: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "detail", rename_all = "snake_case")]
+pub enum ProcessError {
+    /// No runner is tracked for this app id.
+    NotRunning(String),
+    /// The PID no longer exists.
+    NotFound(u32),
+    PermissionDenied(u32),
+    /// The process was still alive after being killed.
+    Timeout(u32),
+    Os { pid: u32, message: String },
+}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessError::NotRunning(app_id) => write!(f, "No running game with app id {}", app_id),
+            ProcessError::NotFound(pid) => write!(f, "Process {} does not exist", pid),
+            ProcessError::PermissionDenied(pid) => {
+                write!(f, "Not allowed to terminate process {}", pid)
+            }
+            ProcessError::Timeout(pid) => write!(f, "Process {} did not exit after being killed", pid),
+            ProcessError::Os { pid, message } => {
+                write!(f, "Failed to terminate process {}: {}", pid, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProcessError {}
+
+/// A runner this app spawned. It is only ever signalled through its `Child`
+/// and only while the child hasn't been reaped, so the PID can't have been
+/// reused by an unrelated process in the meantime.
+#[derive(Debug)]
+pub struct RunnerProcess {
+    pid: u32,
+    child: Mutex<Child>,
+}
+
+impl RunnerProcess {
+    pub fn new(child: Child) -> Self {
+        RunnerProcess {
+            pid: child.id(),
+            child: Mutex::new(child),
+        }
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// The exit status, reaping the child, if it has exited.
+    pub fn try_wait(&self) -> io::Result<Option<ExitStatus>> {
+        match self.child.lock() {
+            Ok(mut child) => child.try_wait(),
+            Err(e) => e.into_inner().try_wait(),
+        }
+    }
+
+    /// Blocks until the child exits. The child is polled rather than waited
+    /// on, so `terminate` can still get at it meanwhile.
+    pub fn wait(&self) -> io::Result<ExitStatus> {
+        loop {
+            if let Some(status) = self.try_wait()? {
+                return Ok(status);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Polls until the child is gone or `timeout` passes. Returns whether it exited.
+    pub async fn wait_for_exit(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if !matches!(self.try_wait(), Ok(None)) {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Ends the child. On Unix it gets SIGTERM and `GRACE_PERIOD` to exit
+    /// before SIGKILL; Windows has no SIGTERM, and the graceful path is the
+    /// runner's control socket, so there it is killed right away.
+    pub async fn terminate(&self) -> Result<(), ProcessError> {
+        #[cfg(unix)]
+        {
+            if self.signal(|_| send_signal(self.pid, libc::SIGTERM))? && self.wait_for_exit(GRACE_PERIOD).await {
+                return Ok(());
+            }
+        }
+
+        let pid = self.pid;
+        let killed = self.signal(|child| {
+            child.kill().map_err(|e| match e.kind() {
+                io::ErrorKind::PermissionDenied => ProcessError::PermissionDenied(pid),
+                _ => ProcessError::Os {
+                    pid,
+                    message: e.to_string(),
+                },
+            })
+        })?;
+        if !killed || self.wait_for_exit(KILL_TIMEOUT).await {
+            Ok(())
+        } else {
+            Err(ProcessError::Timeout(pid))
+        }
+    }
+
+    /// Runs `send` unless the child has already exited, holding the lock so
+    /// it can't be reaped in between. Returns whether `send` ran.
+    fn signal(&self, send: impl FnOnce(&mut Child) -> Result<(), ProcessError>) -> Result<bool, ProcessError> {
+        let mut child = match self.child.lock() {
+            Ok(child) => child,
+            Err(e) => e.into_inner(),
+        };
+        match child.try_wait() {
+            Ok(None) => send(&mut child).map(|()| true),
+            _ => Ok(false),
+        }
+    }
+}
+
+/// Polls until `pid` is gone or `timeout` passes. Returns whether it exited.
+/// For runners left by an earlier run of the app, which have no `Child`.
+pub async fn wait_for_exit(pid: u32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while is_alive(pid) {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    true
+}
+
+/// Asks `pid` to exit with SIGTERM, then SIGKILLs it after `GRACE_PERIOD`.
+/// Only for runners left by an earlier run of the app, once they have been
+/// verified; use `RunnerProcess::terminate` for the ones spawned now.
+#[cfg(unix)]
+pub async fn terminate(pid: u32) -> Result<(), ProcessError> {
+    send_signal(pid, libc::SIGTERM)?;
+    if wait_for_exit(pid, GRACE_PERIOD).await {
+        return Ok(());
+    }
+
+    match send_signal(pid, libc::SIGKILL) {
+        // It exited between the last poll and the kill.
+        Err(ProcessError::NotFound(_)) => return Ok(()),
+        other => other?,
+    }
+    if wait_for_exit(pid, KILL_TIMEOUT).await {
+        Ok(())
+    } else {
+        Err(ProcessError::Timeout(pid))
+    }
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: libc::c_int) -> Result<(), ProcessError> {
+    if unsafe { libc::kill(pid as libc::pid_t, signal) } == 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    Err(match err.raw_os_error() {
+        Some(libc::ESRCH) => ProcessError::NotFound(pid),
+        Some(libc::EPERM) => ProcessError::PermissionDenied(pid),
+        _ => ProcessError::Os {
+            pid,
+            message: err.to_string(),
+        },
+    })
+}
+
+#[cfg(unix)]
+pub fn is_alive(pid: u32) -> bool {
+    // Signal 0 only checks whether the process exists; EPERM still means it does.
+    let ret = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    ret == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Windows has no SIGTERM; the graceful path is the runner's control socket,
+/// so this goes straight to `TerminateProcess` on a handle for `pid`. Only
+/// for verified runners left by an earlier run of the app.
+#[cfg(windows)]
+pub async fn terminate(pid: u32) -> Result<(), ProcessError> {
+    use windows_sys::Win32::Foundation::{CloseHandle, WAIT_OBJECT_0};
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, TerminateProcess, WaitForSingleObject, PROCESS_SYNCHRONIZE, PROCESS_TERMINATE,
+    };
+
+    let handle = unsafe { OpenProcess(PROCESS_TERMINATE | PROCESS_SYNCHRONIZE, 0, pid) };
+    if handle.is_null() {
+        return Err(os_error(pid));
+    }
+
+    let result = unsafe {
+        if TerminateProcess(handle, 1) == 0 {
+            Err(os_error(pid))
+        } else if WaitForSingleObject(handle, KILL_TIMEOUT.as_millis() as u32) == WAIT_OBJECT_0 {
+            Ok(())
+        } else {
+            Err(ProcessError::Timeout(pid))
+        }
+    };
+    unsafe { CloseHandle(handle) };
+    result
+}
+
+#[cfg(windows)]
+fn os_error(pid: u32) -> ProcessError {
+    use windows_sys::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_INVALID_PARAMETER};
+
+    let err = std::io::Error::last_os_error();
+    match err.raw_os_error().map(|code| code as u32) {
+        Some(ERROR_INVALID_PARAMETER) => ProcessError::NotFound(pid),
+        Some(ERROR_ACCESS_DENIED) => ProcessError::PermissionDenied(pid),
+        _ => ProcessError::Os {
+            pid,
+            message: err.to_string(),
+        },
+    }
+}
+
+#[cfg(windows)]
+pub fn is_alive(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::{CloseHandle, WAIT_TIMEOUT};
+    use windows_sys::Win32::System::Threading::{OpenProcess, WaitForSingleObject, PROCESS_SYNCHRONIZE};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_SYNCHRONIZE, 0, pid);
+        if handle.is_null() {
+            return false;
+        }
+        let alive = WaitForSingleObject(handle, 0) == WAIT_TIMEOUT;
+        CloseHandle(handle);
+        alive
+    }
+}
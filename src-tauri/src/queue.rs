@@ -0,0 +1,765 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::commands;
+use crate::control;
+use crate::recovery;
+use crate::sessions::{Session, SessionId, SessionRegistry, SessionStatus};
+
+/// How long each queued game runs unless the UI sets another duration (15m 30s).
+pub const DEFAULT_ITEM_DURATION: u64 = 930;
+/// Queued games are installed in the same folder the UI uses for manual starts.
+const GAME_PATH: &str = "bin";
+
+pub type QueueItemId = u64;
+
+/// What the UI hands over to queue a game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueGame {
+    pub app_id: String,
+    pub name: String,
+    pub executable_name: String,
+    #[serde(default)]
+    pub icon_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueItem {
+    pub id: QueueItemId,
+    #[serde(flatten)]
+    pub game: QueueGame,
+    /// Seconds already played before the app was closed mid-item.
+    #[serde(default)]
+    pub elapsed: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueState {
+    /// Not started, or ran out of items.
+    Idle,
+    Running,
+    /// Does not advance; the current game keeps running.
+    Paused,
+}
+
+/// The item being played and the session it runs in, once launched.
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrentItem {
+    pub item: QueueItem,
+    pub session_id: Option<SessionId>,
+    /// Unix time in milliseconds.
+    pub started_at: Option<u64>,
+}
+
+/// Payload of `queue_changed` and the reply of `get_queue`.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueSnapshot {
+    pub state: QueueState,
+    /// Seconds each item runs for.
+    pub item_duration: u64,
+    pub current: Option<CurrentItem>,
+    /// Items still waiting, in play order.
+    pub items: Vec<QueueItem>,
+    /// Restored from an earlier run and not yet resumed or cleared.
+    pub recovered: bool,
+}
+
+/// The queue as written to disk by `recovery`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedQueue {
+    pub state: QueueState,
+    pub item_duration: u64,
+    /// The item playing, with `elapsed` as of the save.
+    pub current: Option<QueueItem>,
+    pub items: Vec<QueueItem>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionReason {
+    /// Ran for the full item duration.
+    Completed,
+    Skipped,
+    /// Stopped from outside the queue, e.g. with `stop_process`.
+    Stopped,
+    /// The runner ended on its own.
+    Exited,
+    /// Could not be installed or launched.
+    Failed,
+}
+
+/// Payload of `queue_item_completed`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemCompleted {
+    pub item: QueueItem,
+    pub session_id: Option<SessionId>,
+    pub reason: CompletionReason,
+    pub error: Option<String>,
+}
+
+struct Current {
+    item: QueueItem,
+    session: Option<Session>,
+    started: Instant,
+}
+
+struct Inner {
+    state: QueueState,
+    item_duration: u64,
+    items: VecDeque<QueueItem>,
+    current: Option<Current>,
+    recovered: bool,
+    /// Bumped whenever the item timer must be disarmed; a timer only fires
+    /// if the generation it was armed with is still current.
+    generation: u64,
+}
+
+impl Inner {
+    /// Takes the current item and disarms its timer. With `generation`, only
+    /// if that timer is still the armed one.
+    fn take_current(&mut self, generation: Option<u64>) -> Option<Current> {
+        if generation.is_some_and(|g| g != self.generation) {
+            return None;
+        }
+        self.generation += 1;
+        self.current.take()
+    }
+}
+
+/// The quest queue, exposed as Tauri state. It owns its timers, so it keeps
+/// advancing while the webview is throttled or reloading.
+pub struct QueueManager {
+    inner: Mutex<Inner>,
+    next_id: AtomicU64,
+}
+
+impl Default for QueueManager {
+    fn default() -> Self {
+        QueueManager {
+            inner: Mutex::new(Inner {
+                state: QueueState::Idle,
+                item_duration: DEFAULT_ITEM_DURATION,
+                items: VecDeque::new(),
+                current: None,
+                recovered: false,
+                generation: 0,
+            }),
+            next_id: AtomicU64::new(0),
+        }
+    }
+}
+
+impl QueueManager {
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        // Every update leaves the queue consistent, so a poisoned lock is still usable.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn snapshot(&self) -> QueueSnapshot {
+        let inner = self.lock();
+        QueueSnapshot {
+            state: inner.state,
+            item_duration: inner.item_duration,
+            current: inner.current.as_ref().map(|c| CurrentItem {
+                item: c.item.clone(),
+                session_id: c.session.as_ref().map(|s| s.id),
+                started_at: c.session.as_ref().map(|s| s.started_at),
+            }),
+            items: inner.items.iter().cloned().collect(),
+            recovered: inner.recovered,
+        }
+    }
+
+    pub fn to_saved(&self) -> SavedQueue {
+        let inner = self.lock();
+        SavedQueue {
+            state: inner.state,
+            item_duration: inner.item_duration,
+            current: inner.current.as_ref().map(|c| QueueItem {
+                elapsed: c.started.elapsed().as_secs(),
+                ..c.item.clone()
+            }),
+            items: inner.items.iter().cloned().collect(),
+        }
+    }
+
+    /// Puts back a queue saved by an earlier run, paused until the user resumes
+    /// it. The item that was playing goes first, with the time it had already
+    /// played. Restored items go ahead of anything queued since launch, under
+    /// fresh ids; games queued again meanwhile are only kept once.
+    /// Returns whether anything was restored.
+    pub fn restore(&self, saved: SavedQueue) -> bool {
+        let mut inner = self.lock();
+        let untouched = inner.state == QueueState::Idle && inner.current.is_none() && inner.items.is_empty();
+        if untouched {
+            inner.item_duration = saved.item_duration;
+        }
+
+        let mut restored: VecDeque<QueueItem> = VecDeque::new();
+        for item in saved.current.into_iter().chain(saved.items) {
+            let playing = inner.current.as_ref().map(|c| &c.item);
+            let mut queued = inner.items.iter().chain(playing).chain(&restored);
+            if queued.any(|i| i.game.app_id == item.game.app_id) {
+                continue;
+            }
+            restored.push_back(QueueItem {
+                id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+                ..item
+            });
+        }
+        if restored.is_empty() {
+            return false;
+        }
+        let queued_since: Vec<QueueItem> = inner.items.drain(..).collect();
+        restored.extend(queued_since);
+        inner.items = restored;
+
+        if saved.state != QueueState::Idle && inner.state == QueueState::Idle {
+            inner.state = QueueState::Paused;
+            inner.recovered = true;
+        }
+        true
+    }
+
+    /// Appends a game. A game can only be queued or playing once.
+    pub fn enqueue(&self, game: QueueGame) -> Result<QueueItem, String> {
+        let mut inner = self.lock();
+        let playing = inner.current.as_ref().map(|c| &c.item);
+        if inner.items.iter().chain(playing).any(|i| i.game.app_id == game.app_id) {
+            return Err(format!("{} is already in the queue", game.name));
+        }
+        let item = QueueItem {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            game,
+            elapsed: 0,
+        };
+        inner.items.push_back(item.clone());
+        Ok(item)
+    }
+
+    /// Removes a waiting item. Use `skip` for the one playing.
+    pub fn remove(&self, id: QueueItemId) -> Result<QueueItem, String> {
+        let mut inner = self.lock();
+        let index = inner
+            .items
+            .iter()
+            .position(|i| i.id == id)
+            .ok_or_else(|| format!("No queued item with id {}", id))?;
+        Ok(inner.items.remove(index).expect("index was just found"))
+    }
+
+    /// Puts the waiting items in the order of `ids`, which must list each of them once.
+    pub fn reorder(&self, ids: &[QueueItemId]) -> Result<(), String> {
+        let mut inner = self.lock();
+        let mut remaining: Vec<QueueItem> = inner.items.drain(..).collect();
+        let mut ordered = VecDeque::with_capacity(remaining.len());
+        for id in ids {
+            match remaining.iter().position(|i| i.id == *id) {
+                Some(index) => ordered.push_back(remaining.remove(index)),
+                None => {
+                    inner.items = ordered.into_iter().chain(remaining).collect();
+                    return Err(format!("No queued item with id {}", id));
+                }
+            }
+        }
+        if !remaining.is_empty() {
+            inner.items = ordered.into_iter().chain(remaining).collect();
+            return Err("The new order must list every queued item".to_string());
+        }
+        inner.items = ordered;
+        Ok(())
+    }
+
+    /// Drops every waiting item and stops advancing. The current game keeps running.
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        inner.items.clear();
+        inner.recovered = false;
+        inner.generation += 1;
+        inner.state = if inner.current.is_some() {
+            QueueState::Paused
+        } else {
+            QueueState::Idle
+        };
+    }
+
+    pub fn pause(&self) -> Result<(), String> {
+        let mut inner = self.lock();
+        if inner.state != QueueState::Running {
+            return Err("The queue is not running".to_string());
+        }
+        inner.state = QueueState::Paused;
+        inner.generation += 1;
+        Ok(())
+    }
+}
+
+pub fn emit_changed<R: Runtime>(handle: &AppHandle<R>) {
+    let snapshot = handle.state::<QueueManager>().snapshot();
+    let _ = handle.emit("queue_changed", snapshot);
+    recovery::save(handle);
+}
+
+fn emit_completed<R: Runtime>(handle: &AppHandle<R>, completed: ItemCompleted) {
+    log::info!(
+        "Queue item {} ({}) finished: {:?}",
+        completed.item.id, completed.item.game.name, completed.reason
+    );
+    let _ = handle.emit("queue_item_completed", completed);
+    emit_changed(handle);
+}
+
+/// Starts (or resumes) advancing through the queue with `item_duration` per game.
+pub async fn start<R: Runtime>(handle: &AppHandle<R>) {
+    {
+        let queue = handle.state::<QueueManager>();
+        let mut inner = queue.lock();
+        inner.state = QueueState::Running;
+        inner.recovered = false;
+        rearm(handle, &mut inner);
+    }
+    emit_changed(handle);
+    advance(handle).await;
+}
+
+/// Resumes a paused queue. Time the current game ran while paused counts
+/// towards its duration, so it may complete right away.
+pub async fn resume<R: Runtime>(handle: &AppHandle<R>) -> Result<(), String> {
+    if handle.state::<QueueManager>().lock().state != QueueState::Paused {
+        return Err("The queue is not paused".to_string());
+    }
+    start(handle).await;
+    Ok(())
+}
+
+/// Sets how long each game runs. Applies to the current game as well.
+pub async fn set_item_duration<R: Runtime>(handle: &AppHandle<R>, seconds: u64) -> Result<(), String> {
+    if seconds == 0 {
+        return Err("Item duration must be at least one second".to_string());
+    }
+    let extend = {
+        let queue = handle.state::<QueueManager>();
+        let mut inner = queue.lock();
+        let added = seconds.saturating_sub(inner.item_duration);
+        inner.item_duration = seconds;
+        if inner.state == QueueState::Running {
+            rearm(handle, &mut inner);
+        }
+        inner
+            .current
+            .as_ref()
+            .and_then(|c| c.session.as_ref())
+            .filter(|_| added > 0)
+            .map(|session| (session.control_path.clone(), added))
+    };
+    emit_changed(handle);
+
+    // The runner only counts up to the duration it was started with; a
+    // shorter one needs nothing, as the queue stops it when its time is up
+    if let Some((control_path, added)) = extend {
+        control::send(&control_path, &format!("extend-duration {}", added))
+            .await
+            .map_err(|e| format!("The running game's duration could not be extended: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Ends the current item (or drops the next one if nothing is playing) and moves on.
+pub async fn skip<R: Runtime>(handle: &AppHandle<R>) -> Result<(), String> {
+    let skipped = {
+        let queue = handle.state::<QueueManager>();
+        let mut inner = queue.lock();
+        if inner.current.is_none() {
+            let item = inner
+                .items
+                .pop_front()
+                .ok_or_else(|| "The queue is empty".to_string())?;
+            Some(item)
+        } else {
+            None
+        }
+    };
+    match skipped {
+        Some(item) => emit_completed(
+            handle,
+            ItemCompleted {
+                item,
+                session_id: None,
+                reason: CompletionReason::Skipped,
+                error: None,
+            },
+        ),
+        None => finish_current(handle, None, CompletionReason::Skipped).await,
+    }
+    Ok(())
+}
+
+/// Called by the session monitor whenever a runner exits.
+pub async fn session_ended<R: Runtime>(handle: &AppHandle<R>, session_id: SessionId) {
+    let status = handle
+        .state::<SessionRegistry>()
+        .get(session_id)
+        .map(|s| s.status);
+    let ours = {
+        let queue = handle.state::<QueueManager>();
+        let inner = queue.lock();
+        inner
+            .current
+            .as_ref()
+            .and_then(|c| c.session.as_ref())
+            .is_some_and(|s| s.id == session_id)
+    };
+
+    if ours {
+        let reason = if status == Some(SessionStatus::Stopped) {
+            CompletionReason::Stopped
+        } else {
+            CompletionReason::Exited
+        };
+        finish_current(handle, None, reason).await;
+    } else {
+        // Another game was blocking the queue; it may be free to go on now.
+        advance(handle).await;
+    }
+}
+
+/// Arms the timer for the current item with whatever time it has left.
+fn rearm<R: Runtime>(handle: &AppHandle<R>, inner: &mut Inner) {
+    inner.generation += 1;
+    let Some(current) = &inner.current else {
+        return;
+    };
+    if current.session.is_none() {
+        // Still launching; `advance` arms it once the runner is up.
+        return;
+    }
+    let total = Duration::from_secs(inner.item_duration);
+    arm_timer(handle, inner.generation, total.saturating_sub(current.started.elapsed()));
+}
+
+fn arm_timer<R: Runtime>(handle: &AppHandle<R>, generation: u64, delay: Duration) {
+    let handle = handle.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        finish_current(&handle, Some(generation), CompletionReason::Completed).await;
+    });
+}
+
+/// Takes the current item off the queue, stops its runner unless it already
+/// ended, reports it and moves on. With `generation`, does nothing if the
+/// timer that asked for it has since been disarmed.
+async fn finish_current<R: Runtime>(handle: &AppHandle<R>, generation: Option<u64>, reason: CompletionReason) {
+    let current = handle.state::<QueueManager>().lock().take_current(generation);
+    let Some(current) = current else {
+        return;
+    };
+
+    let mut error = None;
+    if let Some(session) = &current.session {
+        if matches!(reason, CompletionReason::Completed | CompletionReason::Skipped) {
+            if let Err(e) = commands::stop_session(handle, session).await {
+                log::warn!("Queue failed to stop runner {}: {}", session.pid, e);
+                error = Some(e.to_string());
+            }
+        }
+    }
+
+    emit_completed(
+        handle,
+        ItemCompleted {
+            item: current.item,
+            session_id: current.session.map(|s| s.id),
+            reason,
+            error,
+        },
+    );
+    advance(handle).await;
+}
+
+/// Launches the next item if the queue is running and nothing else is playing.
+async fn advance<R: Runtime>(handle: &AppHandle<R>) {
+    loop {
+        let (item, duration) = {
+            let queue = handle.state::<QueueManager>();
+            let mut inner = queue.lock();
+            if inner.state != QueueState::Running || inner.current.is_some() {
+                return;
+            }
+            // Wait for a manually started game (or one still being stopped) to end.
+            if handle.state::<SessionRegistry>().list().iter().any(|s| s.is_active()) {
+                return;
+            }
+            let Some(item) = inner.items.pop_front() else {
+                inner.state = QueueState::Idle;
+                drop(inner);
+                log::info!("Queue finished");
+                emit_changed(handle);
+                return;
+            };
+            inner.current = Some(Current {
+                item: item.clone(),
+                session: None,
+                started: started_before(item.elapsed),
+            });
+            // An item interrupted by a restart only plays what it has left.
+            let remaining = inner.item_duration.saturating_sub(item.elapsed).max(1);
+            (item, remaining)
+        };
+        log::info!("Queue starting {} ({})", item.game.name, item.game.app_id);
+        emit_changed(handle);
+
+        match launch(handle, &item, duration).await {
+            Ok(session) => {
+                let orphaned = {
+                    let queue = handle.state::<QueueManager>();
+                    let mut inner = queue.lock();
+                    match inner.current.as_mut() {
+                        Some(current) if current.item.id == item.id => {
+                            current.session = Some(session.clone());
+                            current.started = started_before(item.elapsed);
+                            if inner.state == QueueState::Running {
+                                rearm(handle, &mut inner);
+                            }
+                            false
+                        }
+                        // Skipped while it was launching
+                        _ => true,
+                    }
+                };
+                if orphaned {
+                    let _ = commands::stop_session(handle, &session).await;
+                }
+                emit_changed(handle);
+                return;
+            }
+            Err(e) => {
+                log::warn!("Queue failed to start {}: {}", item.game.name, e);
+                let still_current = {
+                    let queue = handle.state::<QueueManager>();
+                    let mut inner = queue.lock();
+                    let matches = inner.current.as_ref().is_some_and(|c| c.item.id == item.id);
+                    if matches {
+                        inner.current = None;
+                    }
+                    matches
+                };
+                if still_current {
+                    emit_completed(
+                        handle,
+                        ItemCompleted {
+                            item,
+                            session_id: None,
+                            reason: CompletionReason::Failed,
+                            error: Some(e),
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
+
+async fn launch<R: Runtime>(handle: &AppHandle<R>, item: &QueueItem, duration: u64) -> Result<Session, String> {
+    let game = &item.game;
+    // Copying and hashing the runner blocks, so keep it off the async workers
+    let (install, executable_name, app_id) = (handle.clone(), game.executable_name.clone(), game.app_id.clone());
+    tauri::async_runtime::spawn_blocking(move || {
+        commands::install_runner(&install, GAME_PATH, &executable_name, &app_id)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    commands::spawn_runner(
+        handle,
+        &game.name,
+        GAME_PATH,
+        &game.executable_name,
+        game.app_id.clone(),
+        game.icon_url.clone(),
+        Some(duration),
+    )
+}
+
+/// An `Instant` that is already `secs` old.
+fn started_before(secs: u64) -> Instant {
+    let now = Instant::now();
+    now.checked_sub(Duration::from_secs(secs)).unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(app_id: &str) -> QueueGame {
+        QueueGame {
+            app_id: app_id.to_string(),
+            name: format!("Game {}", app_id),
+            executable_name: "game.exe".to_string(),
+            icon_url: None,
+        }
+    }
+
+    fn item(id: QueueItemId, app_id: &str, elapsed: u64) -> QueueItem {
+        QueueItem {
+            id,
+            game: game(app_id),
+            elapsed,
+        }
+    }
+
+    fn waiting(queue: &QueueManager) -> Vec<QueueItemId> {
+        queue.snapshot().items.iter().map(|i| i.id).collect()
+    }
+
+    fn saved(current: Option<QueueItem>, items: Vec<QueueItem>) -> SavedQueue {
+        SavedQueue {
+            state: QueueState::Running,
+            item_duration: 600,
+            current,
+            items,
+        }
+    }
+
+    #[test]
+    fn enqueue_refuses_duplicates() {
+        let queue = QueueManager::default();
+        let first = queue.enqueue(game("1")).unwrap();
+        queue.enqueue(game("2")).unwrap();
+        assert!(queue.enqueue(game("1")).is_err());
+        assert_eq!(waiting(&queue), [first.id, first.id + 1]);
+
+        // Nor may the game playing be queued again
+        let playing = queue.lock().items.pop_front().unwrap();
+        queue.lock().current = Some(Current {
+            item: playing,
+            session: None,
+            started: Instant::now(),
+        });
+        assert!(queue.enqueue(game("1")).is_err());
+        assert!(queue.enqueue(game("3")).is_ok());
+    }
+
+    #[test]
+    fn remove_takes_out_one_item() {
+        let queue = QueueManager::default();
+        let a = queue.enqueue(game("1")).unwrap();
+        let b = queue.enqueue(game("2")).unwrap();
+        assert_eq!(queue.remove(a.id).unwrap().game.app_id, "1");
+        assert_eq!(waiting(&queue), [b.id]);
+        assert!(queue.remove(a.id).is_err());
+        // A removed game can be queued again
+        assert!(queue.enqueue(game("1")).is_ok());
+    }
+
+    #[test]
+    fn reorder_follows_the_given_ids() {
+        let queue = QueueManager::default();
+        let ids: Vec<_> = ["1", "2", "3"].iter().map(|a| queue.enqueue(game(a)).unwrap().id).collect();
+        queue.reorder(&[ids[2], ids[0], ids[1]]).unwrap();
+        assert_eq!(waiting(&queue), [ids[2], ids[0], ids[1]]);
+    }
+
+    #[test]
+    fn reorder_errors_keep_every_item() {
+        let queue = QueueManager::default();
+        let ids: Vec<_> = ["1", "2", "3"].iter().map(|a| queue.enqueue(game(a)).unwrap().id).collect();
+
+        assert!(queue.reorder(&[ids[1], 99]).is_err());
+        let mut left = waiting(&queue);
+        assert_eq!(left[0], ids[1]);
+        left.sort();
+        assert_eq!(left, ids);
+
+        // Leaving an item out is an error too
+        assert!(queue.reorder(&[ids[2], ids[0]]).is_err());
+        let mut left = waiting(&queue);
+        left.sort();
+        assert_eq!(left, ids);
+
+        // So is listing one twice
+        assert!(queue.reorder(&[ids[0], ids[0], ids[1], ids[2]]).is_err());
+        assert_eq!(waiting(&queue).len(), 3);
+    }
+
+    #[test]
+    fn restore_requeues_an_interrupted_item() {
+        let queue = QueueManager::default();
+        assert!(queue.restore(saved(Some(item(5, "1", 120)), vec![item(7, "2", 0)])));
+
+        let snapshot = queue.snapshot();
+        assert_eq!(snapshot.state, QueueState::Paused);
+        assert!(snapshot.recovered);
+        assert_eq!(snapshot.item_duration, 600);
+        assert!(snapshot.current.is_none());
+        let games: Vec<_> = snapshot.items.iter().map(|i| (i.game.app_id.as_str(), i.elapsed)).collect();
+        assert_eq!(games, [("1", 120), ("2", 0)]);
+
+        // Ids stay unique
+        let next = queue.enqueue(game("3")).unwrap().id;
+        assert!(snapshot.items.iter().all(|i| i.id != next));
+    }
+
+    #[test]
+    fn restore_merges_with_games_queued_since_launch() {
+        let queue = QueueManager::default();
+        let early = queue.enqueue(game("2")).unwrap();
+        queue.enqueue(game("3")).unwrap();
+
+        assert!(queue.restore(saved(Some(item(1, "1", 30)), vec![item(2, "2", 0), item(3, "4", 0)])));
+        let snapshot = queue.snapshot();
+        let games: Vec<_> = snapshot.items.iter().map(|i| i.game.app_id.as_str()).collect();
+        assert_eq!(games, ["1", "4", "2", "3"]);
+        // What was queued since launch keeps its id
+        assert_eq!(snapshot.items[2].id, early.id);
+        let mut ids: Vec<_> = snapshot.items.iter().map(|i| i.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 4);
+    }
+
+    #[test]
+    fn restoring_nothing_stays_idle() {
+        let queue = QueueManager::default();
+        assert!(!queue.restore(saved(None, Vec::new())));
+        let snapshot = queue.snapshot();
+        assert_eq!(snapshot.state, QueueState::Idle);
+        assert!(!snapshot.recovered);
+    }
+
+    #[test]
+    fn stale_timers_are_disarmed() {
+        let queue = QueueManager::default();
+        let mut inner = queue.lock();
+        inner.state = QueueState::Running;
+        inner.current = Some(Current {
+            item: item(1, "1", 0),
+            session: None,
+            started: Instant::now(),
+        });
+        let armed = inner.generation;
+        drop(inner);
+
+        // Pausing disarms the timer armed before it
+        queue.pause().unwrap();
+        let mut inner = queue.lock();
+        assert!(inner.take_current(Some(armed)).is_none());
+        assert!(inner.current.is_some());
+
+        // A timer armed afterwards still fires, and only once
+        let rearmed = inner.generation;
+        assert_eq!(inner.take_current(Some(rearmed)).unwrap().item.id, 1);
+        inner.current = Some(Current {
+            item: item(2, "2", 0),
+            session: None,
+            started: Instant::now(),
+        });
+        assert!(inner.take_current(Some(rearmed)).is_none());
+
+        // Skipping ignores the generation
+        assert_eq!(inner.take_current(None).unwrap().item.id, 2);
+    }
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::control::{self, ControlReply};
+use crate::process;
+use crate::queue::{self, QueueManager, SavedQueue};
+use crate::sessions::{self, SessionRegistry};
+use crate::storage;
+
+/// Written to the app data directory on every queue or session change.
+const STATE_FILE: &str = "queue-state.json";
+
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// A runner this app spawned, as written to disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedRunner {
+    name: String,
+    pid: u32,
+    control_path: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedState {
+    /// Unix time in milliseconds.
+    saved_at: u64,
+    queue: SavedQueue,
+    runners: Vec<SavedRunner>,
+}
+
+/// Saves the queue and every live runner so the next launch can pick them up.
+pub fn save<R: Runtime>(handle: &AppHandle<R>) {
+    let Some(path) = storage::app_data_path(handle, STATE_FILE) else {
+        return;
+    };
+    let state = SavedState {
+        saved_at: sessions::now_millis(),
+        queue: handle.state::<QueueManager>().to_saved(),
+        runners: handle
+            .state::<SessionRegistry>()
+            .list()
+            .into_iter()
+            .filter(|s| s.is_active())
+            .map(|s| SavedRunner {
+                name: s.name,
+                pid: s.pid,
+                control_path: s.control_path,
+            })
+            .collect(),
+    };
+    let Ok(json) = serde_json::to_vec_pretty(&state) else {
+        return;
+    };
+
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Err(e) = storage::write_atomic(&path, &json) {
+        log::warn!("Failed to save queue state to {:?}: {}", path, e);
+    }
+}
+
+/// Reads what the previous run left behind. Call it from `setup`, before
+/// anything gets the chance to save over it.
+pub fn load<R: Runtime>(handle: &AppHandle<R>) -> Option<SavedState> {
+    let path = storage::app_data_path(handle, STATE_FILE)?;
+    let data = fs::read(&path).ok()?;
+    match serde_json::from_slice(&data) {
+        Ok(state) => Some(state),
+        Err(e) => {
+            log::warn!("Ignoring unreadable queue state {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Cleans up after the runners in `saved` and restores the queue paused, so
+/// the UI can offer to resume it.
+///
+/// Runners are started with `--parent-pid` and end themselves within a
+/// second of the app going away, so none are adopted: the item that was
+/// playing goes back to the front of the queue with the time it had played.
+/// A runner that is somehow still answering on its socket is stopped.
+pub async fn recover<R: Runtime>(handle: AppHandle<R>, saved: SavedState) {
+    for runner in saved.runners {
+        match verify(&runner).await {
+            Some(_) => {
+                log::info!("Stopping leftover runner {} ({})", runner.pid, runner.name);
+                if control::send(&runner.control_path, "stop").await.is_err()
+                    || !process::wait_for_exit(runner.pid, process::GRACE_PERIOD).await
+                {
+                    if let Err(e) = process::terminate(runner.pid).await {
+                        log::warn!("Failed to stop leftover runner {}: {}", runner.pid, e);
+                    }
+                }
+            }
+            None => {
+                let _ = fs::remove_file(&runner.control_path);
+            }
+        }
+    }
+
+    if handle.state::<QueueManager>().restore(saved.queue) {
+        log::info!("Restored the queue saved at {}", saved.saved_at);
+    }
+    queue::emit_changed(&handle);
+}
+
+/// Whether `runner` is still one of ours. A live PID alone is not enough, since
+/// it may have been reused; the runner has to answer on its control socket
+/// with the same PID.
+async fn verify(runner: &SavedRunner) -> Option<ControlReply> {
+    if !process::is_alive(runner.pid) {
+        return None;
+    }
+    match control::send(&runner.control_path, "status").await {
+        Ok(reply) if reply.pid == Some(runner.pid) => Some(reply),
+        Ok(reply) => {
+            log::info!(
+                "Leaving PID {} alone: its socket is answered by PID {:?}",
+                runner.pid, reply.pid
+            );
+            None
+        }
+        Err(e) => {
+            log::info!("Leaving PID {} alone: {}", runner.pid, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Runtime};
+
+use crate::dummy_games::sha256_file;
+
+/// SHA-256 of the target's runner resource when the app was built; empty if
+/// it wasn't there, as in some development builds.
+const BUILT_SHA256: &str = env!("RUNNER_SHA256");
+
+/// SHA-256 of runners shipped before copies were recorded in a manifest, so
+/// the copies those versions left in game folders are still recognised.
+pub const PREVIOUS_SHA256: &[&str] = &[
+    // `resources/runner.exe` up to the first manifest-writing release
+    "ba2d3ad5ba6bf2de2ff562ea4a3045e106ce0bd649a2517b27ffd999ad309231",
+];
+
+/// Folder in the games root holding one verified copy of each runner build,
+/// which game folders link to. Not an app id, so never listed as a game.
+pub const STORE_DIR: &str = ".runner";
+
+/// How a runner ended up at its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaceMethod {
+    /// An identical copy was already there.
+    Reused,
+    /// Copy-on-write clone sharing the stored copy's blocks.
+    Reflink,
+    /// Second name for the stored copy.
+    HardLink,
+    /// Full copy, where neither link works.
+    Copy,
+}
+
+/// The bundled runner and its checksum.
+pub struct Runner {
+    pub path: PathBuf,
+    pub sha256: String,
+    pub size: u64,
+}
+
+impl Runner {
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        let size = fs::metadata(&path)?.len();
+        let sha256 = sha256_file(&path)?;
+        Ok(Runner { path, sha256, size })
+    }
+
+    /// Whether `path` is a file identical to this runner.
+    pub fn matches(&self, path: &Path) -> bool {
+        fs::symlink_metadata(path).is_ok_and(|m| m.is_file() && m.len() == self.size)
+            && sha256_file(path).is_ok_and(|hash| hash == self.sha256)
+    }
+}
+
+/// The runner bundled with the app, checked against the checksum taken when
+/// the app was built so a damaged or swapped file is never copied out. Debug
+/// builds take `QP_RUNNER` instead if it is set, so a runner built elsewhere
+/// can be tried without copying it into the resources.
+pub fn bundled<R: Runtime>(handle: &AppHandle<R>) -> Result<Runner, String> {
+    #[cfg(debug_assertions)]
+    if let Some(path) = std::env::var_os("QP_RUNNER").map(PathBuf::from) {
+        return Runner::open(path.clone()).map_err(|e| format!("Failed to read runner {:?}: {}", path, e));
+    }
+    let path = crate::commands::runner_resource(handle)?;
+    let runner = Runner::open(path.clone()).map_err(|e| format!("Failed to read runner {:?}: {}", path, e))?;
+    if !BUILT_SHA256.is_empty() && runner.sha256 != BUILT_SHA256 {
+        return Err(format!(
+            "Runner {:?} doesn't match this build of the app (SHA-256 {}, expected {}); reinstall the app",
+            path, runner.sha256, BUILT_SHA256
+        ));
+    }
+    Ok(runner)
+}
+
+/// Puts `runner` at `target`. An identical file already there is kept as
+/// is. Otherwise the target is linked to a copy kept in `<root>/.runner`,
+/// trying a reflink, then a hard link, and copying only when the filesystem
+/// supports neither. Whatever is at `target` must already be known to be
+/// safe to replace. On Unix the copy is made executable.
+pub fn place(runner: &Runner, root: &Path, target: &Path) -> io::Result<PlaceMethod> {
+    let method = place_file(runner, root, target)?;
+    make_executable(target)?;
+    Ok(method)
+}
+
+fn place_file(runner: &Runner, root: &Path, target: &Path) -> io::Result<PlaceMethod> {
+    if runner.matches(target) {
+        return Ok(PlaceMethod::Reused);
+    }
+    remove_existing(target)?;
+
+    let stored = match store(runner, root) {
+        Ok(stored) => stored,
+        Err(e) => {
+            log::warn!("Failed to keep a runner copy in {:?}: {}", root.join(STORE_DIR), e);
+            fs::copy(&runner.path, target)?;
+            return Ok(PlaceMethod::Copy);
+        }
+    };
+    if reflink_copy::reflink(&stored, target).is_ok() {
+        return Ok(PlaceMethod::Reflink);
+    }
+    if fs::hard_link(&stored, target).is_ok() {
+        return Ok(PlaceMethod::HardLink);
+    }
+    fs::copy(&stored, target)?;
+    Ok(PlaceMethod::Copy)
+}
+
+/// Adds the execute bits wherever the file can be read, as resources and
+/// copies may come without them.
+#[cfg(unix)]
+fn make_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = fs::metadata(path)?.permissions();
+    let mode = permissions.mode();
+    let wanted = mode | ((mode & 0o444) >> 2);
+    if wanted != mode {
+        permissions.set_mode(wanted);
+        fs::set_permissions(path, permissions)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn remove_existing(target: &Path) -> io::Result<()> {
+    match fs::remove_file(target) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// `<root>/.runner/<sha256>`, copied from the bundled runner if it is missing
+/// or no longer matches.
+fn store(runner: &Runner, root: &Path) -> io::Result<PathBuf> {
+    let dir = root.join(STORE_DIR);
+    let stored = dir.join(&runner.sha256);
+    if runner.matches(&stored) {
+        return Ok(stored);
+    }
+    fs::create_dir_all(&dir)?;
+    let tmp = dir.join(format!("{}.tmp", runner.sha256));
+    fs::copy(&runner.path, &tmp)?;
+    fs::rename(&tmp, &stored)?;
+    Ok(stored)
+}
+
+/// Deletes stored copies of runner builds other than `keep`. Copies already
+/// linked from game folders live on under their other names.
+pub fn prune_store(root: &Path, keep: &str) {
+    let Ok(entries) = fs::read_dir(root.join(STORE_DIR)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.file_name() == keep {
+            continue;
+        }
+        let path = entry.path();
+        match fs::remove_file(&path) {
+            Ok(()) => log::info!("Removed old runner copy {:?}", path),
+            Err(e) => log::warn!("Failed to remove old runner copy {:?}: {}", path, e),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+use crate::sessions::{self, Session, SessionRegistry};
+
+/// Status events written by the runner with `--status` (see `runner/src/status.rs`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RunnerEvent {
+    Started { pid: u32, title: String, duration: u64 },
+    IconLoaded { url: String },
+    IconFailed { url: String, error: String },
+    Heartbeat { elapsed: u64, duration: u64 },
+    Completed { elapsed: u64 },
+    Exiting { reason: String, code: i32 },
+}
+
+#[derive(Debug, Deserialize)]
+struct StatusLine {
+    ts: u64,
+    #[serde(flatten)]
+    event: RunnerEvent,
+}
+
+/// Payload of the `game_status` event sent to the webview.
+#[derive(Debug, Clone, Serialize)]
+pub struct GameStatusPayload {
+    pub app_id: String,
+    pub pid: u32,
+    /// Unix time in milliseconds at which the runner wrote the event.
+    pub ts: u64,
+    pub status: RunnerEvent,
+}
+
+/// Reads one line of the status stream: its timestamp and event.
+pub fn parse_line(line: &str) -> Option<(u64, RunnerEvent)> {
+    serde_json::from_str::<StatusLine>(line)
+        .ok()
+        .map(|l| (l.ts, l.event))
+}
+
+/// Reads the runner's status stream until it closes, folds every event into
+/// the session registry and forwards it as `game_status`. Blocks, so run it
+/// on its own thread.
+pub fn forward<R: Runtime, S: Read>(handle: &AppHandle<R>, session: &Session, stream: S) {
+    let registry = handle.state::<SessionRegistry>();
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let Some((ts, status)) = parse_line(&line) else {
+            log::info!("[runner {}] {}", session.pid, line);
+            continue;
+        };
+
+        if let Some((updated, reason)) = registry.apply_runner_event(session.id, &status) {
+            sessions::emit_change(handle, updated, reason);
+        }
+        let _ = handle.emit(
+            "game_status",
+            GameStatusPayload {
+                app_id: session.app_id.clone(),
+                pid: session.pid,
+                ts,
+                status,
+            },
+        );
+    }
+}
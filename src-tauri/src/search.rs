@@ -0,0 +1,358 @@
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::catalog::{DetectableApp, GameDetails};
+
+/// Results per page when the caller doesn't say.
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+/// Largest page `search_games` will return.
+pub const MAX_PAGE_SIZE: usize = 200;
+
+/// Which part of a game a search matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchField {
+    Name,
+    Alias,
+    Executable,
+}
+
+/// How a search term matched, best first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchKind {
+    Exact,
+    Prefix,
+    Substring,
+    /// Within one or two typos of the start of the text.
+    Typo,
+    /// Every character of the query appears, in order.
+    Subsequence,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub game: DetectableApp,
+    /// Higher is better; only meaningful within one search.
+    pub score: u32,
+    pub matched_field: MatchField,
+    /// The name, alias or executable that matched.
+    pub matched_text: String,
+}
+
+/// Returned by `search_games`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchPage {
+    pub query: String,
+    /// Matches across all pages.
+    pub total: usize,
+    pub offset: usize,
+    pub hits: Vec<SearchHit>,
+}
+
+/// A searchable string with the key it is compared by.
+struct Term {
+    text: String,
+    key: String,
+    field: MatchField,
+}
+
+struct Entry {
+    game: DetectableApp,
+    terms: Vec<Term>,
+}
+
+/// Search keys for every catalog entry, computed once per catalog load so a
+/// keystroke only costs a scan over short pre-folded strings.
+#[derive(Default)]
+pub struct SearchIndex {
+    entries: Vec<Entry>,
+}
+
+impl SearchIndex {
+    pub fn build(details: &[GameDetails]) -> Self {
+        let entries = details
+            .iter()
+            .map(|d| {
+                let game = &d.game;
+                let mut terms = vec![term(&game.name, &game.name, MatchField::Name)];
+                terms.extend(game.aliases.iter().map(|a| term(a, a, MatchField::Alias)));
+                terms.extend(
+                    game.executables
+                        .iter()
+                        .map(|e| term(&e.name, executable_stem(&e.name), MatchField::Executable)),
+                );
+                // Keys that fold to nothing, or repeat an earlier one, can only add noise
+                let mut seen = HashSet::new();
+                terms.retain(|t| !t.key.is_empty() && seen.insert(t.key.clone()));
+                Entry {
+                    game: game.clone(),
+                    terms,
+                }
+            })
+            .collect();
+        SearchIndex { entries }
+    }
+
+    /// Games matching `query`, best first. Names, aliases and executable file
+    /// names are compared ignoring case, punctuation, spaces and a file
+    /// extension, so "genshin" and "GenshinImpact.exe" both find Genshin Impact.
+    /// An empty query lists the whole catalog by name.
+    pub fn search(&self, query: &str, offset: usize, limit: usize) -> SearchPage {
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+        let key = fold(executable_stem(query.trim()));
+
+        let mut hits: Vec<(u32, &Entry, &Term)> = if key.is_empty() {
+            self.entries
+                .iter()
+                .filter_map(|e| e.terms.first().map(|t| (0, e, t)))
+                .collect()
+        } else {
+            self.entries
+                .iter()
+                .filter_map(|e| {
+                    e.terms
+                        .iter()
+                        .filter_map(|t| score(&key, t).map(|s| (s, t)))
+                        .max_by_key(|(s, _)| *s)
+                        .map(|(s, t)| (s, e, t))
+                })
+                .collect()
+        };
+        // Among equal scores the shorter name is the closer match; with no
+        // query at all, plain name order
+        let by_length = !key.is_empty();
+        hits.sort_by_cached_key(|(score, entry, _)| {
+            let name = &entry.game.name;
+            (std::cmp::Reverse(*score), if by_length { name.len() } else { 0 }, name.to_lowercase())
+        });
+
+        SearchPage {
+            query: query.to_string(),
+            total: hits.len(),
+            offset,
+            hits: hits
+                .into_iter()
+                .skip(offset)
+                .take(limit)
+                .map(|(score, entry, term)| SearchHit {
+                    game: entry.game.clone(),
+                    score,
+                    matched_field: term.field,
+                    matched_text: term.text.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn term(text: &str, key_source: &str, field: MatchField) -> Term {
+    Term {
+        text: text.to_string(),
+        key: fold(key_source),
+        field,
+    }
+}
+
+/// Lowercase letters and digits only.
+fn fold(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// `win64/GenshinImpact.exe` -> `GenshinImpact`. Anything without a
+/// directory or extension comes back unchanged.
+fn executable_stem(name: &str) -> &str {
+    let file = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    match file.rsplit_once('.') {
+        Some((stem, ext))
+            if !stem.is_empty()
+                && (1..=4).contains(&ext.len())
+                && ext.chars().all(|c| c.is_ascii_alphanumeric())
+                && ext.chars().any(|c| c.is_ascii_alphabetic()) =>
+        {
+            stem
+        }
+        _ => file,
+    }
+}
+
+fn score(query: &str, term: &Term) -> Option<u32> {
+    let kind = match_kind(query, &term.key)?;
+    let base = match kind {
+        MatchKind::Exact => 1000,
+        MatchKind::Prefix => 800,
+        MatchKind::Substring => 600,
+        MatchKind::Typo => 400,
+        MatchKind::Subsequence => 200,
+    };
+    let weight = match term.field {
+        MatchField::Name => 100,
+        MatchField::Alias => 90,
+        MatchField::Executable => 80,
+    };
+    // Prefer terms the query covers more of
+    let coverage = (query.len() * 50 / term.key.len().max(1)) as u32;
+    Some(base * weight / 100 + coverage.min(50))
+}
+
+fn match_kind(query: &str, key: &str) -> Option<MatchKind> {
+    if key == query {
+        return Some(MatchKind::Exact);
+    }
+    if key.starts_with(query) {
+        return Some(MatchKind::Prefix);
+    }
+    if key.contains(query) {
+        return Some(MatchKind::Substring);
+    }
+    let query_len = query.chars().count();
+    let allowed = match query_len {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    };
+    if allowed > 0 {
+        let head: String = key.chars().take(query_len).collect();
+        if edit_distance(query, &head) <= allowed {
+            return Some(MatchKind::Typo);
+        }
+    }
+    if query_len >= 3 && is_subsequence(query, key) {
+        return Some(MatchKind::Subsequence);
+    }
+    None
+}
+
+fn is_subsequence(query: &str, key: &str) -> bool {
+    let mut key = key.chars();
+    query.chars().all(|q| key.any(|k| k == q))
+}
+
+/// Levenshtein distance, counting a swap of two neighbours as one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::{CatalogBuilder, Executable};
+
+    fn app(id: usize, name: &str, executables: &[&str]) -> DetectableApp {
+        DetectableApp {
+            id: id.to_string(),
+            name: name.to_string(),
+            aliases: Vec::new(),
+            icon: None,
+            splash: None,
+            executables: executables
+                .iter()
+                .map(|e| Executable {
+                    os: "win32".to_string(),
+                    name: e.to_string(),
+                    is_launcher: false,
+                    arguments: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn index(apps: Vec<DetectableApp>) -> SearchIndex {
+        let mut builder = CatalogBuilder::default();
+        builder.add("test", apps);
+        SearchIndex::build(&builder.finish())
+    }
+
+    fn names(page: &SearchPage) -> Vec<&str> {
+        page.hits.iter().map(|h| h.game.name.as_str()).collect()
+    }
+
+    #[test]
+    fn finds_games_by_name_or_executable() {
+        let index = index(vec![
+            app(1, "Genshin Impact", &["GenshinImpact.exe"]),
+            app(2, "Honkai: Star Rail", &["StarRail.exe"]),
+        ]);
+        for query in ["genshin", "GenshinImpact.exe", "genshin impact", "GENSHIN"] {
+            let page = index.search(query, 0, 10);
+            assert_eq!(names(&page), ["Genshin Impact"], "{:?}", query);
+        }
+
+        let page = index.search("StarRail.exe", 0, 10);
+        assert_eq!(names(&page), ["Honkai: Star Rail"]);
+        assert_eq!(page.hits[0].matched_field, MatchField::Executable);
+        assert_eq!(page.hits[0].matched_text, "StarRail.exe");
+    }
+
+    #[test]
+    fn ranks_by_match_kind() {
+        let index = index(vec![
+            app(1, "Post Royal Tale", &[]),
+            app(2, "Protal Knights", &[]),
+            app(3, "Bridge Portal", &[]),
+            app(4, "Portal 2", &[]),
+            app(5, "Portal", &[]),
+            app(6, "Minecraft", &[]),
+        ]);
+        let page = index.search("portal", 0, 10);
+        // Exact, prefix, substring, typo, subsequence
+        assert_eq!(
+            names(&page),
+            ["Portal", "Portal 2", "Bridge Portal", "Protal Knights", "Post Royal Tale"]
+        );
+        assert_eq!(page.total, 5);
+        assert!(page.hits.windows(2).all(|w| w[0].score > w[1].score));
+    }
+
+    #[test]
+    fn short_queries_need_no_typos() {
+        let index = index(vec![app(1, "Dota 2", &[])]);
+        assert_eq!(index.search("doat", 0, 10).total, 1);
+        assert_eq!(index.search("dat", 0, 10).total, 0);
+    }
+
+    #[test]
+    fn clamps_limit_and_offset() {
+        let apps = (0..250).map(|i| app(i, &format!("Game {:03}", i), &[])).collect();
+        let index = index(apps);
+
+        let page = index.search("", 0, 10_000);
+        assert_eq!(page.total, 250);
+        assert_eq!(page.hits.len(), MAX_PAGE_SIZE);
+        assert_eq!(page.hits[0].game.name, "Game 000");
+
+        assert_eq!(index.search("", 0, 0).hits.len(), 1);
+
+        let page = index.search("", 240, 50);
+        assert_eq!(page.hits.len(), 10);
+        assert_eq!(page.hits[0].game.name, "Game 240");
+
+        let page = index.search("", 1_000, 50);
+        assert!(page.hits.is_empty());
+        assert_eq!(page.total, 250);
+        assert_eq!(page.offset, 1_000);
+    }
+}
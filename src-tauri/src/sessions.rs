@@ -0,0 +1,209 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Runtime};
+
+use crate::process::RunnerProcess;
+use crate::recovery;
+use crate::runner_status::RunnerEvent;
+
+/// Finished sessions kept around for `list_sessions` before the oldest are dropped.
+const FINISHED_HISTORY: usize = 50;
+
+pub type SessionId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    Running,
+    /// A stop was requested and the runner has not exited yet.
+    Stopping,
+    /// Ended by `stop_process`.
+    Stopped,
+    /// Ended on its own (completed, closed by the user or crashed).
+    Exited,
+}
+
+/// One runner launched by `start_game_process`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Session {
+    pub id: SessionId,
+    pub app_id: String,
+    pub name: String,
+    pub pid: u32,
+    pub exe_path: PathBuf,
+    /// Unix time in milliseconds.
+    pub started_at: u64,
+    pub status: SessionStatus,
+    /// Seconds played, as last reported by the runner.
+    pub elapsed: u64,
+    /// Target duration in seconds, as last reported by the runner.
+    pub duration: Option<u64>,
+    /// Whether the runner reported reaching its target duration.
+    pub completed: bool,
+    pub exit_code: Option<i32>,
+    #[serde(skip)]
+    pub control_path: PathBuf,
+    /// The spawned runner, which is only ever stopped through this.
+    #[serde(skip)]
+    pub process: Arc<RunnerProcess>,
+}
+
+impl Session {
+    pub fn is_active(&self) -> bool {
+        matches!(self.status, SessionStatus::Running | SessionStatus::Stopping)
+    }
+}
+
+/// Why a `session_changed` event was sent.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeReason {
+    Started,
+    Progress,
+    Completed,
+    Stopping,
+    Stopped,
+    Exited,
+}
+
+/// Payload of `session_changed` and `game_exited`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionEvent {
+    pub session: Session,
+    pub reason: ChangeReason,
+}
+
+/// Every runner session of this app run, exposed as Tauri state so the
+/// webview can recover its view after a reload.
+#[derive(Default)]
+pub struct SessionRegistry {
+    sessions: Mutex<HashMap<SessionId, Session>>,
+    next_id: AtomicU64,
+}
+
+impl SessionRegistry {
+    pub fn insert(
+        &self,
+        app_id: String,
+        name: String,
+        process: Arc<RunnerProcess>,
+        exe_path: PathBuf,
+        control_path: PathBuf,
+    ) -> Session {
+        let session = Session {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            app_id,
+            name,
+            pid: process.pid(),
+            exe_path,
+            started_at: now_millis(),
+            status: SessionStatus::Running,
+            elapsed: 0,
+            duration: None,
+            completed: false,
+            exit_code: None,
+            control_path,
+            process,
+        };
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.insert(session.id, session.clone());
+        }
+        session
+    }
+
+    pub fn get(&self, id: SessionId) -> Option<Session> {
+        self.sessions.lock().ok()?.get(&id).cloned()
+    }
+
+    /// All sessions, oldest first.
+    pub fn list(&self) -> Vec<Session> {
+        let mut sessions: Vec<Session> = match self.sessions.lock() {
+            Ok(sessions) => sessions.values().cloned().collect(),
+            Err(_) => Vec::new(),
+        };
+        sessions.sort_by_key(|s| s.id);
+        sessions
+    }
+
+    /// The live session for `app_id`, if any.
+    pub fn active_for(&self, app_id: &str) -> Option<Session> {
+        self.list()
+            .into_iter()
+            .rev()
+            .find(|s| s.app_id == app_id && s.is_active())
+    }
+
+    /// Applies `change` to a session and returns the updated copy.
+    pub fn update(&self, id: SessionId, change: impl FnOnce(&mut Session)) -> Option<Session> {
+        let mut sessions = self.sessions.lock().ok()?;
+        let session = sessions.get_mut(&id)?;
+        change(session);
+        let updated = session.clone();
+
+        if !updated.is_active() {
+            prune_finished(&mut sessions);
+        }
+        Some(updated)
+    }
+
+    /// Folds a runner status event into the session. Returns the change worth
+    /// reporting, if any.
+    pub fn apply_runner_event(&self, id: SessionId, event: &RunnerEvent) -> Option<(Session, ChangeReason)> {
+        let reason = match event {
+            RunnerEvent::Started { .. } | RunnerEvent::Heartbeat { .. } => ChangeReason::Progress,
+            RunnerEvent::Completed { .. } => ChangeReason::Completed,
+            _ => return None,
+        };
+        let session = self.update(id, |s| match event {
+            RunnerEvent::Started { duration, .. } => s.duration = Some(*duration),
+            RunnerEvent::Heartbeat { elapsed, duration } => {
+                s.elapsed = *elapsed;
+                s.duration = Some(*duration);
+            }
+            RunnerEvent::Completed { elapsed } => {
+                s.elapsed = *elapsed;
+                s.completed = true;
+            }
+            _ => {}
+        })?;
+        Some((session, reason))
+    }
+}
+
+fn prune_finished(sessions: &mut HashMap<SessionId, Session>) {
+    let mut finished: Vec<SessionId> = sessions
+        .values()
+        .filter(|s| !s.is_active())
+        .map(|s| s.id)
+        .collect();
+    if finished.len() <= FINISHED_HISTORY {
+        return;
+    }
+    finished.sort_unstable();
+    for id in &finished[..finished.len() - FINISHED_HISTORY] {
+        sessions.remove(id);
+    }
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+pub fn emit_change<R: Runtime>(handle: &AppHandle<R>, session: Session, reason: ChangeReason) {
+    let event = SessionEvent { session, reason };
+    if matches!(reason, ChangeReason::Stopped | ChangeReason::Exited) {
+        let _ = handle.emit("game_exited", event.clone());
+    }
+    let _ = handle.emit("session_changed", event);
+    // Only the set of running runners is saved, so progress doesn't change it
+    if matches!(reason, ChangeReason::Started | ChangeReason::Stopped | ChangeReason::Exited) {
+        recovery::save(handle);
+    }
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+
+use crate::http::HttpSettings;
+use crate::sources::{self, SourceConfig};
+use crate::storage;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Backend settings, stored as JSON in the app data directory. Missing fields
+/// take their default, so older files keep loading as settings are added.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// How long a cached catalog is served without asking the server again.
+    pub catalog_max_age_secs: u64,
+    /// Where the game list comes from. Relative file and directory paths are
+    /// taken from the app data directory.
+    pub catalog_sources: Vec<SourceConfig>,
+    /// Timeouts, retries, proxy and certificates for catalog downloads.
+    pub http: HttpSettings,
+    /// Where dummy games are created instead of the default. A relative path
+    /// is taken from the data directory.
+    pub games_root: Option<PathBuf>,
+    /// Game folders unused for this many days are removed at startup; 0 keeps them.
+    pub games_retention_days: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            catalog_max_age_secs: 24 * 60 * 60,
+            catalog_sources: sources::default_sources(),
+            http: HttpSettings::default(),
+            games_root: None,
+            games_retention_days: 30,
+        }
+    }
+}
+
+impl Settings {
+    pub fn catalog_max_age(&self) -> Duration {
+        Duration::from_secs(self.catalog_max_age_secs)
+    }
+
+    pub fn games_retention(&self) -> Option<Duration> {
+        (self.games_retention_days > 0).then(|| Duration::from_secs(u64::from(self.games_retention_days) * 24 * 60 * 60))
+    }
+
+    pub fn fetch_deadline(&self) -> Duration {
+        Duration::from_secs(self.http.fetch_deadline_secs)
+    }
+}
+
+/// The current settings, exposed as Tauri state.
+pub struct SettingsStore {
+    settings: Mutex<Settings>,
+    path: Option<PathBuf>,
+}
+
+impl SettingsStore {
+    /// Reads the settings file, falling back to defaults if it is missing or unreadable.
+    pub fn load<R: Runtime>(handle: &AppHandle<R>) -> Self {
+        let path = storage::app_data_path(handle, SETTINGS_FILE);
+        let settings = path
+            .as_ref()
+            .and_then(|p| fs::read(p).ok())
+            .and_then(|data| match serde_json::from_slice(&data) {
+                Ok(settings) => Some(settings),
+                Err(e) => {
+                    log::warn!("Ignoring unreadable settings file: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        SettingsStore {
+            settings: Mutex::new(settings),
+            path,
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_default()
+    }
+
+    /// Replaces the settings and writes them to disk.
+    pub fn set(&self, settings: Settings) -> Result<(), String> {
+        if let Some(path) = &self.path {
+            let json = serde_json::to_vec_pretty(&settings).map_err(|e| e.to_string())?;
+            storage::write_atomic(path, &json)
+                .map_err(|e| format!("Failed to save settings to {:?}: {}", path, e))?;
+        }
+        if let Ok(mut current) = self.settings.lock() {
+            *current = settings;
+        }
+        Ok(())
+    }
+}
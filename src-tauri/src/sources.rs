@@ -0,0 +1,439 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tokio::task::JoinSet;
+
+use crate::catalog::{self, Catalog, CatalogBuilder, DetectableApp, GameDetails, SourceFormat};
+use crate::catalog_cache::{CatalogCache, Fetched, Freshness, SourceError};
+use crate::diagnostics::{self, FetchReport, SourceReport, SourceStatus};
+use crate::http::RetryPolicy;
+use crate::sessions::now_millis;
+
+pub const DISCORD_URL: &str = "https://discord.com/api/applications/detectable";
+pub const GIST_URL: &str = "https://gist.githubusercontent.com/DeadSix27/b8e377c9fed6d98bff22dcdf8807e207/raw/52d1f2d31be7168a0486a3a355e06a2d751bdc44/gameslist.json";
+
+/// Where a source reads its entries from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SourceLocation {
+    /// A JSON list served over HTTP(S), cached on disk.
+    Http { url: String },
+    /// A JSON file holding a list of entries.
+    File { path: PathBuf },
+    /// Every `*.json` file in a directory, each holding a list or a single entry.
+    Directory { path: PathBuf },
+}
+
+/// One catalog source, as listed in the settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceConfig {
+    pub name: String,
+    #[serde(flatten)]
+    pub location: SourceLocation,
+    pub format: SourceFormat,
+    /// Lower goes first. For an id listed by several sources, the first one
+    /// supplies the name and gets first pick of icon and splash.
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// The sources used when the settings don't list any.
+pub fn default_sources() -> Vec<SourceConfig> {
+    vec![
+        SourceConfig {
+            name: "Discord".to_string(),
+            location: SourceLocation::Http {
+                url: DISCORD_URL.to_string(),
+            },
+            format: SourceFormat::Discord,
+            priority: 0,
+            enabled: true,
+        },
+        SourceConfig {
+            name: "Extended gist".to_string(),
+            location: SourceLocation::Http {
+                url: GIST_URL.to_string(),
+            },
+            format: SourceFormat::Gist,
+            priority: 10,
+            enabled: true,
+        },
+    ]
+}
+
+/// Local sources get this long past the deadline before they are given up on,
+/// since they have no timeout of their own.
+const DEADLINE_GRACE: Duration = Duration::from_secs(2);
+
+/// Shared by every source during one catalog load.
+pub struct FetchContext {
+    pub client: reqwest::Client,
+    pub cache: CatalogCache,
+    pub max_age: Duration,
+    /// Skip the cache and revalidate with the server.
+    pub force: bool,
+    /// Retries for HTTP sources, and when the whole load must be done by.
+    pub policy: RetryPolicy,
+}
+
+pub type FetchFuture<'a> = Pin<Box<dyn Future<Output = Result<Fetched, SourceError>> + Send + 'a>>;
+
+/// Something that provides raw catalog entries in one `SourceFormat`.
+pub trait CatalogSource: Send + Sync {
+    fn name(&self) -> &str;
+    /// URL or path, for diagnostics.
+    fn location(&self) -> String;
+    fn format(&self) -> SourceFormat;
+    fn fetch<'a>(&'a self, ctx: &'a FetchContext) -> FetchFuture<'a>;
+}
+
+pub struct HttpJsonSource {
+    name: String,
+    url: String,
+    format: SourceFormat,
+}
+
+impl CatalogSource for HttpJsonSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn location(&self) -> String {
+        self.url.clone()
+    }
+
+    fn format(&self) -> SourceFormat {
+        self.format
+    }
+
+    fn fetch<'a>(&'a self, ctx: &'a FetchContext) -> FetchFuture<'a> {
+        Box::pin(ctx.cache.fetch(&ctx.client, &self.url, self.format, ctx.max_age, ctx.force, ctx.policy))
+    }
+}
+
+pub struct FileSource {
+    name: String,
+    path: PathBuf,
+    format: SourceFormat,
+}
+
+impl CatalogSource for FileSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn format(&self) -> SourceFormat {
+        self.format
+    }
+
+    fn fetch<'a>(&'a self, _ctx: &'a FetchContext) -> FetchFuture<'a> {
+        Box::pin(async move {
+            let (entries, modified) = read_json_file(&self.path).await?;
+            Ok(Fetched {
+                entries,
+                freshness: Freshness::Fresh,
+                fetched_at: modified,
+                http_status: None,
+                warnings: Vec::new(),
+            })
+        })
+    }
+}
+
+pub struct DirectorySource {
+    name: String,
+    path: PathBuf,
+    format: SourceFormat,
+}
+
+impl CatalogSource for DirectorySource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn format(&self) -> SourceFormat {
+        self.format
+    }
+
+    fn fetch<'a>(&'a self, _ctx: &'a FetchContext) -> FetchFuture<'a> {
+        Box::pin(async move {
+            let mut dir = tokio::fs::read_dir(&self.path)
+                .await
+                .map_err(|e| format!("Failed to read {:?}: {}", self.path, e))?;
+            let mut files = Vec::new();
+            while let Ok(Some(entry)) = dir.next_entry().await {
+                let path = entry.path();
+                if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+                    files.push(path);
+                }
+            }
+            // Read in name order so the same directory always merges the same way
+            files.sort();
+
+            let mut entries = Vec::new();
+            let mut warnings = Vec::new();
+            let mut oldest: Option<u64> = None;
+            for file in files {
+                match read_json_file(&file).await {
+                    Ok((list, modified)) => {
+                        entries.extend(list);
+                        oldest = Some(oldest.map_or(modified, |t| t.min(modified)));
+                    }
+                    Err(e) => warnings.push(format!("Skipped {}", e)),
+                }
+            }
+            Ok(Fetched {
+                entries,
+                freshness: Freshness::Fresh,
+                fetched_at: oldest.unwrap_or_else(now_millis),
+                http_status: None,
+                warnings,
+            })
+        })
+    }
+}
+
+/// Reads a JSON file holding a list of entries or a single entry. Returns the
+/// entries and the file's modification time in Unix milliseconds.
+async fn read_json_file(path: &Path) -> Result<(Vec<Value>, u64), String> {
+    let data = tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    let entries = match serde_json::from_slice::<Value>(&data) {
+        Ok(Value::Array(list)) => list,
+        Ok(entry @ Value::Object(_)) => vec![entry],
+        Ok(_) => return Err(format!("{:?} is not a JSON list or object", path)),
+        Err(e) => return Err(format!("Invalid JSON in {:?}: {}", path, e)),
+    };
+    let modified = tokio::fs::metadata(path)
+        .await
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_else(now_millis);
+    Ok((entries, modified))
+}
+
+/// Builds the source described by `config`. Relative paths are taken from `base_dir`.
+pub fn build(config: &SourceConfig, base_dir: Option<&Path>) -> Arc<dyn CatalogSource> {
+    let resolve = |path: &PathBuf| match base_dir {
+        Some(base) if path.is_relative() => base.join(path),
+        _ => path.clone(),
+    };
+    let name = config.name.clone();
+    let format = config.format;
+    match &config.location {
+        SourceLocation::Http { url } => Arc::new(HttpJsonSource {
+            name,
+            url: url.clone(),
+            format,
+        }),
+        SourceLocation::File { path } => Arc::new(FileSource {
+            name,
+            path: resolve(path),
+            format,
+        }),
+        SourceLocation::Directory { path } => Arc::new(DirectorySource {
+            name,
+            path: resolve(path),
+            format,
+        }),
+    }
+}
+
+/// The merged catalog along with each entry's provenance.
+pub struct LoadedCatalog {
+    pub catalog: Catalog,
+    pub details: Vec<GameDetails>,
+}
+
+/// Passed to `load_catalog`'s callback each time a source finishes.
+pub struct LoadProgress<'a> {
+    pub report: &'a SourceReport,
+    /// Sources finished so far, this one included.
+    pub done: usize,
+    pub total: usize,
+    /// What the sources finished so far merge into, in priority order.
+    pub partial: &'a [GameDetails],
+}
+
+/// The usable part of a source's result.
+struct Loaded {
+    apps: Vec<DetectableApp>,
+    freshness: Freshness,
+    fetched_at: u64,
+}
+
+/// Normalises what a source returned and describes it for the fetch report.
+fn evaluate(
+    source: &dyn CatalogSource,
+    result: Result<Fetched, SourceError>,
+    took: Duration,
+) -> (SourceReport, Option<Loaded>) {
+    let mut report = SourceReport {
+        name: source.name().to_string(),
+        location: source.location(),
+        format: source.format(),
+        status: SourceStatus::Failed,
+        http_status: None,
+        latency_ms: took.as_millis() as u64,
+        entries: 0,
+        accepted: 0,
+        malformed: 0,
+        malformed_samples: Vec::new(),
+        warnings: Vec::new(),
+        error: None,
+    };
+
+    match result {
+        Ok(fetched) => {
+            report.status = match fetched.freshness {
+                Freshness::Fresh => SourceStatus::Fresh,
+                Freshness::Cached => SourceStatus::Cached,
+                Freshness::Stale => SourceStatus::Stale,
+            };
+            report.http_status = fetched.http_status;
+            report.entries = fetched.entries.len();
+            report.warnings = fetched.warnings;
+
+            let (apps, malformed) = catalog::normalize_list(source.format(), fetched.entries);
+            report.accepted = apps.len();
+            report.malformed = malformed.len();
+            report.malformed_samples = malformed.into_iter().take(diagnostics::MALFORMED_SAMPLES).collect();
+            let loaded = Loaded {
+                apps,
+                freshness: fetched.freshness,
+                fetched_at: fetched.fetched_at,
+            };
+            (report, Some(loaded))
+        }
+        Err(e) => {
+            report.http_status = e.http_status;
+            report.error = Some(e.message);
+            (report, None)
+        }
+    }
+}
+
+/// Merges the sources that have loaded so far, in the order of `sources`.
+fn merge(sources: &[Arc<dyn CatalogSource>], loaded: &[Option<Loaded>]) -> Vec<GameDetails> {
+    let mut builder = CatalogBuilder::default();
+    for (source, loaded) in sources.iter().zip(loaded) {
+        if let Some(loaded) = loaded {
+            builder.add(source.name(), loaded.apps.clone());
+        }
+    }
+    builder.finish()
+}
+
+/// Loads every enabled source in parallel, each giving up at the context's
+/// deadline, and merges them into one catalog in priority order. `progress`
+/// is called as each source finishes. Fails only if none of them produced
+/// anything; the report covers every source either way.
+pub async fn load_catalog(
+    sources: &[Arc<dyn CatalogSource>],
+    ctx: Arc<FetchContext>,
+    mut progress: impl FnMut(LoadProgress<'_>),
+) -> (Result<LoadedCatalog, String>, FetchReport) {
+    let started_at = now_millis();
+    let started = Instant::now();
+
+    let deadline = ctx.policy.deadline + DEADLINE_GRACE;
+    let mut tasks = JoinSet::new();
+    for (i, source) in sources.iter().enumerate() {
+        let source = source.clone();
+        let ctx = ctx.clone();
+        tasks.spawn(async move {
+            let started = Instant::now();
+            let result = match tokio::time::timeout_at(deadline, source.fetch(&ctx)).await {
+                Ok(result) => result,
+                Err(_) => Err(SourceError::from(format!("Timed out reading {}", source.location()))),
+            };
+            (i, result, started.elapsed())
+        });
+    }
+
+    let mut loaded: Vec<Option<Loaded>> = sources.iter().map(|_| None).collect();
+    let mut reports: Vec<Option<SourceReport>> = sources.iter().map(|_| None).collect();
+    let mut done = 0;
+    while let Some(joined) = tasks.join_next().await {
+        let (i, result, took) = match joined {
+            Ok(finished) => finished,
+            Err(e) => {
+                log::warn!("Catalog source task failed: {}", e);
+                continue;
+            }
+        };
+        let (report, source_loaded) = evaluate(sources[i].as_ref(), result, took);
+        report.log();
+        loaded[i] = source_loaded;
+        done += 1;
+        progress(LoadProgress {
+            report: &report,
+            done,
+            total: sources.len(),
+            partial: &merge(sources, &loaded),
+        });
+        reports[i] = Some(report);
+    }
+
+    let reports: Vec<SourceReport> = sources
+        .iter()
+        .zip(reports)
+        .map(|(source, report)| {
+            report.unwrap_or_else(|| {
+                let failed = Err(SourceError::from("Source task failed".to_string()));
+                evaluate(source.as_ref(), failed, started.elapsed()).0
+            })
+        })
+        .collect();
+    let errors: Vec<String> = reports
+        .iter()
+        .filter_map(|r| r.error.as_ref().map(|e| format!("{}: {}", r.name, e)))
+        .collect();
+
+    let details = merge(sources, &loaded);
+    let report = FetchReport {
+        started_at,
+        duration_ms: started.elapsed().as_millis() as u64,
+        games: details.len(),
+        sources: reports,
+    };
+    let loaded: Vec<&Loaded> = loaded.iter().flatten().collect();
+    let Some(updated_at) = loaded.iter().map(|l| l.fetched_at).min() else {
+        return (Err(format!("No game list available: {}", errors.join("; "))), report);
+    };
+
+    let catalog = Catalog {
+        total: details.len(),
+        freshness: loaded.iter().map(|l| l.freshness).max().unwrap_or(Freshness::Fresh),
+        updated_at: Some(updated_at),
+    };
+    (Ok(LoadedCatalog { catalog, details }), report)
+}
+
+/// The enabled sources from `configs`, sorted by priority.
+pub fn enabled(configs: &[SourceConfig], base_dir: Option<&Path>) -> Vec<Arc<dyn CatalogSource>> {
+    let mut configs: Vec<&SourceConfig> = configs.iter().filter(|c| c.enabled).collect();
+    configs.sort_by_key(|c| c.priority);
+    configs.into_iter().map(|c| build(c, base_dir)).collect()
+}
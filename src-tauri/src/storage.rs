@@ -0,0 +1,43 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// A file with this name next to the executable turns on portable mode:
+/// everything Quest Passer writes then stays beside it, in `data/` and `games/`.
+pub const PORTABLE_MARKER: &str = "portable";
+
+/// The directory holding the running executable.
+pub fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe().ok()?.parent().map(Path::to_path_buf)
+}
+
+pub fn is_portable() -> bool {
+    exe_dir().is_some_and(|dir| dir.join(PORTABLE_MARKER).is_file())
+}
+
+/// Where settings, caches and state are kept: `data/` next to the executable
+/// in portable mode, else the platform's app data directory.
+pub fn data_dir<R: Runtime>(handle: &AppHandle<R>) -> Option<PathBuf> {
+    if is_portable() {
+        return exe_dir().map(|dir| dir.join("data"));
+    }
+    handle.path().app_data_dir().ok()
+}
+
+/// `name` inside the data directory, if the platform has one.
+pub fn app_data_path<R: Runtime>(handle: &AppHandle<R>, name: &str) -> Option<PathBuf> {
+    data_dir(handle).map(|dir| dir.join(name))
+}
+
+/// Writes `data` to `path` through a temporary file and a rename, so a crash
+/// mid-write never leaves a torn file behind.
+pub fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, data)?;
+    fs::rename(&tmp, path)
+}
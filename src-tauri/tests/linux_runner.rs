@@ -0,0 +1,450 @@
+//! Runs the real runner through the same steps the app takes on a Linux
+//! desktop: create the dummy game, start it, follow its status, then stop it,
+//! by hand and from the queue.
+#![cfg(target_os = "linux")]
+
+use app_lib::catalog::{CatalogStore, DetectableApp, Executable};
+use app_lib::commands::{self, runner_command};
+use app_lib::control::{self, ControlReply};
+use app_lib::dummy_games;
+use app_lib::executables::{self, HostOs};
+use app_lib::game_paths::GamePaths;
+use app_lib::process::{self, ProcessError, RunnerProcess};
+use app_lib::queue::{self, QueueGame, QueueManager, QueueState};
+use app_lib::runner_copy::{self, PlaceMethod, Runner};
+use app_lib::runner_status::{self, RunnerEvent};
+use app_lib::sessions::{Session, SessionId, SessionRegistry, SessionStatus};
+use app_lib::settings::{Settings, SettingsStore};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+use tauri::{App, AppHandle, Listener, Manager};
+
+const APP_ID: &str = "1158877933042143272";
+const GAME_PATH: &str = "bin";
+const EVENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The runner crate, built once for all tests. `QP_TEST_RUNNER` points at an
+/// existing build instead.
+fn runner_binary() -> &'static Path {
+    static RUNNER: OnceLock<PathBuf> = OnceLock::new();
+    RUNNER.get_or_init(|| {
+        if let Some(path) = std::env::var_os("QP_TEST_RUNNER") {
+            return PathBuf::from(path);
+        }
+        let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("runner");
+        let status = Command::new(env!("CARGO"))
+            .args(["build", "--quiet", "--manifest-path"])
+            .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("../runner/Cargo.toml"))
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .status()
+            .expect("failed to run cargo");
+        assert!(status.success(), "building the runner failed");
+        target_dir.join("debug").join("runner")
+    })
+}
+
+/// A fresh, empty games folder for one test.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("qp-linux-runner-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn game() -> DetectableApp {
+    let executable = |os: &str, name: &str| Executable {
+        os: os.to_string(),
+        name: name.to_string(),
+        is_launcher: false,
+        arguments: None,
+    };
+    DetectableApp {
+        id: APP_ID.to_string(),
+        name: "Test Game".to_string(),
+        aliases: Vec::new(),
+        icon: None,
+        splash: None,
+        executables: vec![
+            executable("win32", "testgame.exe"),
+            executable("linux", "testgame.x86_64"),
+        ],
+    }
+}
+
+/// Creates the dummy game through `create_dummy_game`, under the executable
+/// name picked for Linux, and returns the runner copy.
+async fn create(handle: &AppHandle<MockRuntime>) -> PathBuf {
+    let resolved = executables::resolve(&game(), HostOs::Linux);
+    let name = &resolved.candidates[0].name;
+    commands::create_dummy_game(handle.clone(), GAME_PATH, name, APP_ID.to_string())
+        .await
+        .unwrap();
+    GamePaths::for_app(handle).unwrap().folder(APP_ID, GAME_PATH).unwrap().join(name)
+}
+
+/// Does what `start_game_process` does, and returns the child with a channel
+/// of its status events.
+fn start(root: &Path, target: &Path, duration: Option<u64>) -> (Child, Receiver<RunnerEvent>, PathBuf) {
+    let folder = GamePaths::new(root).folder(APP_ID, GAME_PATH).unwrap();
+    let control_path = control::new_socket_path();
+    let mut child = runner_command(target, &folder, "Test Game", None, duration, &control_path)
+        .spawn()
+        .unwrap();
+    let stdout = child.stdout.take().unwrap();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if let Some((_, event)) = runner_status::parse_line(&line) {
+                let _ = tx.send(event);
+            }
+        }
+    });
+    (child, rx, control_path)
+}
+
+/// The first event matching `wanted`, failing after `EVENT_TIMEOUT`.
+fn wait_for(events: &Receiver<RunnerEvent>, wanted: impl Fn(&RunnerEvent) -> bool) -> RunnerEvent {
+    let deadline = Instant::now() + EVENT_TIMEOUT;
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        let event = events.recv_timeout(left).expect("runner event did not arrive");
+        if wanted(&event) {
+            return event;
+        }
+    }
+}
+
+/// The runner's `status` reply, once its control socket is up.
+async fn wait_for_control(control_path: &Path) -> ControlReply {
+    let deadline = Instant::now() + EVENT_TIMEOUT;
+    loop {
+        match control::send(control_path, "status").await {
+            Ok(reply) => return reply,
+            Err(e) if Instant::now() > deadline => panic!("runner never answered: {}", e),
+            Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+        }
+    }
+}
+
+/// The app's state on a mock runtime, creating games in `root`. Settings and
+/// the saved queue go to a data folder of its own, and the runner built above
+/// stands in for the bundled one.
+fn app(root: &Path) -> App<MockRuntime> {
+    // Set once, before any test reads them; each app gets its own data folder
+    // through its identifier instead
+    static ENV: OnceLock<()> = OnceLock::new();
+    ENV.get_or_init(|| {
+        std::env::set_var("XDG_DATA_HOME", scratch("data"));
+        std::env::set_var("QP_RUNNER", runner_binary());
+    });
+    let mut context = mock_context(noop_assets());
+    context.config_mut().identifier = format!("test.questpasser.{}", root.file_name().unwrap().to_string_lossy());
+    let app = mock_builder()
+        .manage(SessionRegistry::default())
+        .manage(QueueManager::default())
+        .manage(CatalogStore::default())
+        .build(context)
+        .unwrap();
+    let settings = SettingsStore::load(app.handle());
+    settings
+        .set(Settings {
+            games_root: Some(root.to_path_buf()),
+            ..Settings::default()
+        })
+        .unwrap();
+    app.manage(settings);
+    app
+}
+
+/// The session once `wanted` holds for it, failing after `EVENT_TIMEOUT`.
+async fn wait_for_session(
+    handle: &AppHandle<MockRuntime>,
+    id: SessionId,
+    wanted: impl Fn(&Session) -> bool,
+) -> Session {
+    let deadline = Instant::now() + EVENT_TIMEOUT;
+    loop {
+        let session = handle.state::<SessionRegistry>().get(id).expect("session is gone");
+        if wanted(&session) {
+            return session;
+        }
+        assert!(Instant::now() < deadline, "session never got there: {:?}", session);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+#[tokio::test]
+async fn creates_an_executable_copy_named_for_linux() {
+    let root = scratch("create");
+    let app = app(&root);
+    let target = create(app.handle()).await;
+    assert_eq!(target.file_name().unwrap(), "testgame.x86_64");
+    assert_ne!(fs::metadata(&target).unwrap().permissions().mode() & 0o111, 0);
+
+    // Creating it again keeps the copy, which is recognised as ours
+    let runner = Runner::open(runner_binary().to_path_buf()).unwrap();
+    assert_eq!(runner_copy::place(&runner, &root, &target).unwrap(), PlaceMethod::Reused);
+    let game_dir = root.join(APP_ID);
+    assert!(dummy_games::is_replaceable(&game_dir, &target, None));
+
+    // Anything else in the way is not
+    let other = game_dir.join(GAME_PATH).join("save.dat");
+    fs::write(&other, b"not a runner").unwrap();
+    assert!(!dummy_games::is_replaceable(&game_dir, &other, Some(&runner.sha256)));
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn starts_reports_and_stops_gracefully() {
+    let root = scratch("stop");
+    let app = app(&root);
+    let target = create(app.handle()).await;
+    let (mut child, events, control_path) = start(&root, &target, None);
+
+    let started = wait_for(&events, |e| matches!(e, RunnerEvent::Started { .. }));
+    let RunnerEvent::Started { pid, title, .. } = started else {
+        unreachable!()
+    };
+    assert_eq!(pid, child.id());
+    assert_eq!(title, "Test Game");
+    // Discord matches on the process's executable
+    assert_eq!(fs::read_link(format!("/proc/{}/exe", pid)).unwrap(), target.canonicalize().unwrap());
+
+    let status = wait_for_control(&control_path).await;
+    assert_eq!(status.pid, Some(pid));
+    control::send(&control_path, "set-title Renamed").await.unwrap();
+    let status = control::send(&control_path, "status").await.unwrap();
+    assert_eq!(status.title.as_deref(), Some("Renamed"));
+
+    control::send(&control_path, "stop").await.unwrap();
+    let exiting = wait_for(&events, |e| matches!(e, RunnerEvent::Exiting { .. }));
+    assert!(matches!(exiting, RunnerEvent::Exiting { code: 0, .. }));
+    assert_eq!(child.wait().unwrap().code(), Some(0));
+    assert!(!control_path.exists());
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn terminate_ends_a_runner_through_its_child() {
+    let root = scratch("terminate-child");
+    let app = app(&root);
+    let target = create(app.handle()).await;
+    let (child, events, _control_path) = start(&root, &target, None);
+    wait_for(&events, |e| matches!(e, RunnerEvent::Started { .. }));
+
+    // As in the app, a monitor reaps the runner once it exits
+    let runner = Arc::new(RunnerProcess::new(child));
+    let monitor = {
+        let runner = runner.clone();
+        thread::spawn(move || runner.wait().unwrap().code())
+    };
+    runner.terminate().await.unwrap();
+    assert_eq!(monitor.join().unwrap(), Some(128 + libc::SIGTERM));
+
+    // Once reaped, its PID is never signalled again
+    runner.terminate().await.unwrap();
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn terminate_ends_a_leftover_runner() {
+    let root = scratch("terminate");
+    let app = app(&root);
+    let target = create(app.handle()).await;
+    let (mut child, events, _control_path) = start(&root, &target, None);
+    wait_for(&events, |e| matches!(e, RunnerEvent::Started { .. }));
+
+    // As in the app, a monitor reaps the runner once it exits
+    let pid = child.id();
+    let monitor = thread::spawn(move || child.wait().unwrap().code());
+    process::terminate(pid).await.unwrap();
+    assert_eq!(monitor.join().unwrap(), Some(128 + libc::SIGTERM));
+    assert!(!process::is_alive(pid));
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn reports_completion_after_the_duration() {
+    let root = scratch("duration");
+    let app = app(&root);
+    let target = create(app.handle()).await;
+    let (mut child, events, control_path) = start(&root, &target, Some(1));
+
+    // The session is marked completed when this arrives
+    wait_for(&events, |e| matches!(e, RunnerEvent::Completed { .. }));
+    control::send(&control_path, "stop").await.unwrap();
+    assert_eq!(child.wait().unwrap().code(), Some(0));
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn removes_the_folder_of_a_game_that_was_played() {
+    let root = scratch("remove");
+    let app = app(&root);
+    let target = create(app.handle()).await;
+    let games = GamePaths::new(&root);
+    let game_dir = games.game_dir(APP_ID).unwrap();
+    dummy_games::record_use(&game_dir, APP_ID, "Test Game").unwrap();
+    let (mut child, _events, control_path) = start(&root, &target, None);
+    wait_for_control(&control_path).await;
+
+    // Kept while the game runs
+    let busy = HashSet::from([APP_ID.to_string()]);
+    assert!(dummy_games::remove_folder(&games, APP_ID, None, &busy).is_err());
+
+    control::send(&control_path, "stop").await.unwrap();
+    child.wait().unwrap();
+    // A debug log anywhere but beside the runner copy is someone else's
+    let stray = game_dir.join("logs");
+    fs::create_dir(&stray).unwrap();
+    fs::write(stray.join("runner_debug.txt"), b"Runner started!\n").unwrap();
+    assert!(dummy_games::remove_folder(&games, APP_ID, None, &HashSet::new()).is_err());
+    fs::remove_dir_all(&stray).unwrap();
+    // Older Windows runners left their debug log behind
+    fs::write(target.with_file_name("runner_debug.txt"), b"Runner started!\n").unwrap();
+
+    let freed = dummy_games::remove_folder(&games, APP_ID, None, &HashSet::new()).unwrap();
+    assert!(freed > 0);
+    assert!(!game_dir.exists());
+    // The runner copy is linked to the one in `.runner`, which stays
+    let stored = root.join(runner_copy::STORE_DIR).join(Runner::open(runner_binary().to_path_buf()).unwrap().sha256);
+    assert!(stored.exists());
+    assert!(freed < fs::metadata(&stored).unwrap().len());
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn keeps_folders_holding_other_files() {
+    let root = scratch("keep");
+    let app = app(&root);
+    let target = create(app.handle()).await;
+    let games = GamePaths::new(&root);
+    fs::write(target.with_file_name("save.dat"), b"progress").unwrap();
+
+    let error = dummy_games::remove_folder(&games, APP_ID, None, &HashSet::new()).unwrap_err();
+    assert!(error.contains("save.dat"), "{}", error);
+    assert!(target.exists());
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn stop_process_ends_a_started_game() {
+    let root = scratch("app-stop");
+    let app = app(&root);
+    let handle = app.handle().clone();
+    create(&handle).await;
+
+    let session = commands::start_game_process(
+        handle.clone(),
+        "Test Game",
+        GAME_PATH,
+        "testgame.x86_64",
+        APP_ID.to_string(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(handle.state::<SessionRegistry>().active_for(APP_ID).map(|s| s.id), Some(session.id));
+    wait_for_control(&session.control_path).await;
+
+    commands::stop_process(handle.clone(), handle.state(), APP_ID.to_string()).await.unwrap();
+    let stopped = wait_for_session(&handle, session.id, |s| !s.is_active()).await;
+    assert_eq!(stopped.status, SessionStatus::Stopped);
+    assert_eq!(stopped.exit_code, Some(0));
+    assert!(!session.control_path.exists());
+
+    // Nothing is left to stop
+    let error = commands::stop_process(handle.clone(), handle.state(), APP_ID.to_string()).await.unwrap_err();
+    assert!(matches!(error, ProcessError::NotRunning(_)));
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn queue_plays_each_game_in_turn() {
+    let root = scratch("app-queue");
+    let app = app(&root);
+    let handle = app.handle().clone();
+    let completed = Arc::new(Mutex::new(Vec::new()));
+    let seen = completed.clone();
+    handle.listen("queue_item_completed", move |event| {
+        seen.lock().unwrap().push(event.payload().to_string());
+    });
+
+    // The queue installs each game itself
+    let app_ids = [APP_ID, "1158877933042143273"];
+    for app_id in app_ids {
+        let game = QueueGame {
+            app_id: app_id.to_string(),
+            name: "Test Game".to_string(),
+            executable_name: "testgame.x86_64".to_string(),
+            icon_url: None,
+        };
+        commands::queue_enqueue(handle.clone(), handle.state(), game).unwrap();
+    }
+    queue::set_item_duration(&handle, 1).await.unwrap();
+    queue::start(&handle).await;
+
+    // The last completion and the queue going idle may come in either order
+    let deadline = Instant::now() + EVENT_TIMEOUT;
+    while completed.lock().unwrap().len() < app_ids.len()
+        || handle.state::<QueueManager>().snapshot().state != QueueState::Idle
+    {
+        assert!(Instant::now() < deadline, "the queue never finished");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    // One after the other, each stopped once its time was up
+    let sessions = handle.state::<SessionRegistry>().list();
+    let played: Vec<&str> = sessions.iter().map(|s| s.app_id.as_str()).collect();
+    assert_eq!(played, app_ids);
+    for session in &sessions {
+        let ended = wait_for_session(&handle, session.id, |s| !s.is_active()).await;
+        assert_eq!(ended.status, SessionStatus::Stopped);
+    }
+    let completed = completed.lock().unwrap();
+    assert!(completed.iter().all(|c| c.contains(r#""reason":"completed""#)), "{:?}", completed);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn queue_extends_the_playing_game_when_its_duration_grows() {
+    let root = scratch("app-queue-extend");
+    let app = app(&root);
+    let handle = app.handle().clone();
+    let game = QueueGame {
+        app_id: APP_ID.to_string(),
+        name: "Test Game".to_string(),
+        executable_name: "testgame.x86_64".to_string(),
+        icon_url: None,
+    };
+    commands::queue_enqueue(handle.clone(), handle.state(), game).unwrap();
+    queue::set_item_duration(&handle, 60).await.unwrap();
+    queue::start(&handle).await;
+
+    let session = handle.state::<SessionRegistry>().active_for(APP_ID).expect("the queue started nothing");
+    assert_eq!(wait_for_control(&session.control_path).await.duration, Some(60));
+    queue::set_item_duration(&handle, 90).await.unwrap();
+    let reply = control::send(&session.control_path, "status").await.unwrap();
+    assert_eq!(reply.duration, Some(90));
+
+    // Shortening it is left to the queue's own timer
+    queue::set_item_duration(&handle, 30).await.unwrap();
+    let reply = control::send(&session.control_path, "status").await.unwrap();
+    assert_eq!(reply.duration, Some(90));
+
+    queue::skip(&handle).await.unwrap();
+    let ended = wait_for_session(&handle, session.id, |s| !s.is_active()).await;
+    assert_eq!(ended.status, SessionStatus::Stopped);
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -1,14 +1,47 @@
 import { useState, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
-import type { Game, RunningGame, Settings } from "./types";
-import { Search, Play, Square, Loader2, Settings as SettingsIcon, X, Plus, Trash2 } from "lucide-react";
+import type { BackendSettings, Catalog, CatalogProgress, DummyGame, DummyGameRemoval, FetchReport, Game, GameDetails, GamesRoot, GameStatusPayload, ProcessError, QueueGame, ResolvedExecutable, QueueItemCompleted, QueueSnapshot, RunningGame, SearchHit, SearchPage, Session, SessionEvent, Settings } from "./types";
+import { Search, Play, Square, Loader2, Settings as SettingsIcon, X, Plus, Trash2, ChevronUp, SkipForward, RefreshCw } from "lucide-react";
+
+// What the backend needs to install and launch a game
+async function toQueueGame(game: Game, nameOverride?: string): Promise<QueueGame> {
+    // The backend picks the executable Discord will match on this OS
+    const resolved = await invoke<ResolvedExecutable>("resolve_executable", {
+        app_id: game.id,
+        fallback_name: game.name
+    });
+    return {
+        app_id: game.id,
+        name: nameOverride || game.name,
+        executable_name: resolved.candidates[0].name,
+        icon_url: game.icon
+            ? `https://cdn.discordapp.com/app-icons/${game.id}/${game.icon}.png?size=64`
+            : null
+    };
+}
+
+function toRunningGame(session: Session): RunningGame {
+    return {
+        id: session.app_id,
+        name: session.name,
+        executable_name: session.exe_path.split(/[\\/]/).pop() || session.exe_path,
+        pid: session.pid,
+        startTime: session.started_at
+    };
+}
 
 
 function App() {
-    const [games, setGames] = useState<Game[]>([]);
     const [search, setSearch] = useState("");
     const [loading, setLoading] = useState(true);
+    const [catalogInfo, setCatalogInfo] = useState<Catalog | null>(null);
+    const [catalogProgress, setCatalogProgress] = useState<CatalogProgress | null>(null);
+    const [backendSettings, setBackendSettings] = useState<BackendSettings | null>(null);
+    const [gamesRoot, setGamesRoot] = useState<GamesRoot | null>(null);
+    const [dummyGames, setDummyGames] = useState<DummyGame[] | null>(null);
+    const [fetchReport, setFetchReport] = useState<FetchReport | null>(null);
+    const [showDiagnostics, setShowDiagnostics] = useState(false);
     const [runningGame, setRunningGame] = useState<RunningGame | null>(null);
     const [settings, setSettings] = useState<Settings>(() => {
         const saved = localStorage.getItem("qp-settings");
@@ -16,9 +49,9 @@ function App() {
     });
     const [showSettings, setShowSettings] = useState(false);
 
-    // Quest Queue
-    const [queue, setQueue] = useState<Game[]>([]);
-    const [isQueueRunning, setIsQueueRunning] = useState(false);
+    // Quest Queue, owned by the backend
+    const [queue, setQueue] = useState<QueueSnapshot | null>(null);
+    const queuedItems = queue?.items ?? [];
     const [isStarting, setIsStarting] = useState(false);
 
 
@@ -33,61 +66,121 @@ function App() {
         localStorage.setItem("qp-settings", JSON.stringify(settings));
     }, [settings]);
 
+    useEffect(() => {
+        invoke("queue_set_item_duration", { seconds: settings.queueTimerDuration || 930 })
+            .catch(e => console.error("Failed to set queue duration", e));
+    }, [settings.queueTimerDuration]);
+
     useEffect(() => {
         localStorage.setItem("qp-custom-games", JSON.stringify(customGames));
     }, [customGames]);
 
     useEffect(() => {
         // Listen for process exit from backend
-        const unlisten = listen("game_exited", () => {
-            console.log("Game exited event received");
-            setRunningGame(null);
+        const unlisten = listen<SessionEvent>("game_exited", (event) => {
+            const { app_id } = event.payload.session;
+            setRunningGame(current => current && current.id === app_id ? null : current);
         });
 
-        // Load games
-        async function loadGames() {
-            try {
-                const json = await invoke<string>("fetch_game_list");
-                const data = JSON.parse(json);
-                setGames(data);
-            } catch (e) {
-                console.error("Failed to load games", e);
-            } finally {
-                setLoading(false);
-            }
-        }
-        loadGames();
+        // Re-attach to a runner that is still alive after a webview reload
+        invoke<Session[]>("list_sessions").then(sessions => {
+            const live = [...sessions].reverse().find(s => s.status === "running");
+            if (!live) return;
+            setRunningGame(current => current ?? toRunningGame(live));
+        }).catch(e => console.error("Failed to list sessions", e));
+
+        // Games launched by the queue show up here
+        const unlistenSessions = listen<SessionEvent>("session_changed", (event) => {
+            if (event.payload.reason !== "started") return;
+            const session = event.payload.session;
+            setRunningGame(current => current ?? toRunningGame(session));
+        });
+
+        invoke<QueueSnapshot>("get_queue").then(setQueue)
+            .catch(e => console.error("Failed to load queue", e));
+        const unlistenQueue = listen<QueueSnapshot>("queue_changed", (event) => setQueue(event.payload));
+        const unlistenCompleted = listen<QueueItemCompleted>("queue_item_completed", (event) => {
+            const { item, reason, error } = event.payload;
+            if (reason === "failed") console.error(`Queue failed to start ${item.name}`, error);
+        });
+
+        // Runner progress: re-anchor the start time on the runner's own clock
+        const unlistenStatus = listen<GameStatusPayload>("game_status", (event) => {
+            const { app_id, pid, ts, status } = event.payload;
+            if (status.event !== "started" && status.event !== "heartbeat") return;
+            const elapsed = status.event === "heartbeat" ? status.elapsed : 0;
+            setRunningGame(current => current && current.id === app_id
+                ? { ...current, pid, startTime: ts - elapsed * 1000 }
+                : current);
+        });
+
+        // Sources report in as they finish; on first load their games are searchable straight away
+        const unlistenProgress = listen<CatalogProgress>("catalog_progress", (event) => setCatalogProgress(event.payload));
+
+        loadGames(false);
+        invoke<BackendSettings>("get_settings").then(setBackendSettings)
+            .catch(e => console.error("Failed to load settings", e));
+        loadGamesRoot();
 
         return () => {
             unlisten.then(f => f());
-        };
-        return () => {
-            unlisten.then(f => f());
+            unlistenStatus.then(f => f());
+            unlistenSessions.then(f => f());
+            unlistenQueue.then(f => f());
+            unlistenCompleted.then(f => f());
+            unlistenProgress.then(f => f());
         };
     }, []);
 
-    // Queue Logic: Auto-play next game when runningGame becomes null
-    // Queue Logic: Auto-play next game when runningGame becomes null
-    useEffect(() => {
-        // Only run if queue is enabled, nothing is running, and we aren't already starting a game
-        if (isQueueRunning && !runningGame && !isStarting) {
-            if (queue.length > 0) {
-                const nextGame = queue[0];
-                console.log("Auto-starting next queued game:", nextGame.name);
-
-                // Immediately update queue to prevent picking same game twice
-                // And set isStarting to block effect from re-running
-                setQueue(currentQueue => currentQueue.slice(1));
-
-                // Trigger start
-                startGame(nextGame);
-            } else {
-                // Queue finished
-                console.log("Queue finished");
-                setIsQueueRunning(false);
-            }
+    // Load games; the backend serves its cache unless a refresh is forced
+    const loadGames = useCallback(async (forceRefresh: boolean) => {
+        setLoading(true);
+        setCatalogProgress(null);
+        try {
+            setCatalogInfo(await invoke<Catalog>("fetch_game_list", { force_refresh: forceRefresh }));
+        } catch (e) {
+            console.error("Failed to load games", e);
+            setCatalogInfo(null);
+        } finally {
+            setLoading(false);
+            setCatalogProgress(null);
+            invoke<FetchReport | null>("get_fetch_report").then(setFetchReport)
+                .catch(e => console.error("Failed to load fetch report", e));
         }
-    }, [runningGame, isQueueRunning, queue, isStarting]);
+    }, []);
+
+    const updateBackendSettings = async (changes: Partial<BackendSettings>) => {
+        if (!backendSettings) return;
+        try {
+            setBackendSettings(await invoke<BackendSettings>("update_settings", {
+                new_settings: { ...backendSettings, ...changes }
+            }));
+            if ("games_root" in changes) loadGamesRoot();
+        } catch (e) {
+            console.error("Failed to save settings", e);
+        }
+    };
+
+    const loadDummyGames = () => {
+        invoke<DummyGame[]>("list_dummy_games").then(setDummyGames)
+            .catch(e => console.error("Failed to list dummy games", e));
+    };
+
+    const removeDummyGames = async (appIds: string[]) => {
+        try {
+            const removals = await invoke<DummyGameRemoval[]>("remove_dummy_games", { app_ids: appIds });
+            const kept = removals.filter(r => r.error);
+            if (kept.length > 0) alert(kept.map(r => `${r.app_id}: ${r.error}`).join("\n"));
+        } catch (e) {
+            console.error("Failed to remove dummy games", e);
+        }
+        loadDummyGames();
+    };
+
+    const loadGamesRoot = () => {
+        invoke<GamesRoot>("get_games_root").then(setGamesRoot)
+            .catch(e => console.error("Failed to resolve games folder", e));
+    };
 
     // Keyboard Shortcuts
     useEffect(() => {
@@ -106,43 +199,72 @@ function App() {
         if (!runningGame) return;
         console.log("Stopping game:", runningGame.name);
         try {
-            await invoke("stop_process", { exec_name: runningGame.executable_name });
+            await invoke("stop_process", { app_id: runningGame.id });
             setRunningGame(null);
         } catch (e) {
             console.error("Failed to stop game", e);
-            alert(`Failed to stop game: ${e}`);
+            const error = e as ProcessError;
+            if (error.kind === "not_running" || error.kind === "not_found") {
+                // Already gone, nothing left to stop
+                setRunningGame(null);
+                return;
+            }
+            alert(`Failed to stop game: ${error.kind ? `${error.kind} (${JSON.stringify(error.detail)})` : e}`);
         }
     }, [runningGame]);
 
-    // Queue Supervisor: Auto-stop game after 15m 30s if queue is running
-    useEffect(() => {
-        if (!runningGame || !isQueueRunning) return;
-
-        const interval = setInterval(() => {
-            const elapsedSeconds = Math.floor((Date.now() - runningGame.startTime) / 1000);
-
-            // Dynamic duration from settings (default 15m 30s)
-            const limit = settings.queueTimerDuration || 930;
-            if (elapsedSeconds >= limit) {
-                console.log("Queue Supervisor: Time limit reached, stopping game to proceed to next.");
-                stopGame(); // This will trigger the 'game_exited' listener -> runningGame=null -> Queue Logic picks next
-            }
-        }, 5000); // Check every 5s
+    const extendGame = useCallback(async (seconds: number) => {
+        if (!runningGame) return;
+        try {
+            await invoke("extend_game_duration", { app_id: runningGame.id, seconds });
+        } catch (e) {
+            console.error("Failed to extend game", e);
+        }
+    }, [runningGame]);
 
-        return () => clearInterval(interval);
-    }, [runningGame, isQueueRunning, settings.queueTimerDuration, stopGame]);
+    // Catalog search runs in the backend; custom games are only known here.
+    // Search again whenever more of the catalog becomes searchable.
+    const publishedSources = catalogProgress?.published ? catalogProgress.done : 0;
+    const [searchHits, setSearchHits] = useState<SearchHit[]>([]);
+    const [searchTotal, setSearchTotal] = useState(0);
+    useEffect(() => {
+        const query = search.trim();
+        if (query === "") {
+            setSearchHits([]);
+            setSearchTotal(0);
+            return;
+        }
+        let cancelled = false;
+        invoke<SearchPage>("search_games", { query, offset: 0, limit: 50 })
+            .then(page => {
+                if (cancelled) return;
+                setSearchHits(page.hits);
+                setSearchTotal(page.total);
+            })
+            .catch(e => console.error("Search failed", e));
+        return () => { cancelled = true; };
+    }, [search, catalogInfo, publishedSources]);
 
-    // Derived filtered games
-    const filteredGames = search.trim() === ""
+    const customMatches = search.trim() === ""
         ? []
-        : [...customGames, ...games].filter((g) => g.name.toLowerCase().includes(search.toLowerCase()));
+        : customGames.filter((g) => g.name.toLowerCase().includes(search.trim().toLowerCase()));
+    const filteredGames = [...customMatches, ...searchHits.map(hit => hit.game)];
+    const matchNotes = new Map(searchHits
+        .filter(hit => hit.matched_field !== "name")
+        .map(hit => [hit.game.id, `Matched ${hit.matched_field} ${hit.matched_text}`]));
 
     const [selectedGame, setSelectedGame] = useState<Game | null>(null);
     const [customGameName, setCustomGameName] = useState("");
+    const [selectedDetails, setSelectedDetails] = useState<GameDetails | null>(null);
 
     const handlePlayClick = (game: Game) => {
         setSelectedGame(game);
         setCustomGameName(game.name);
+        // Custom games are not in the catalog, so there are no details for them
+        setSelectedDetails(null);
+        invoke<GameDetails>("get_game_details", { app_id: game.id })
+            .then(setSelectedDetails)
+            .catch(() => setSelectedDetails(null));
     };
 
     const handleDeleteGame = (e: React.MouseEvent, gameId: string) => {
@@ -164,37 +286,24 @@ function App() {
         setIsStarting(true); // Lock
 
         try {
-            const finalName = nameOverride || game.name;
-            // Find executable name for win32
-            const execName = game.executables?.find(e => e.os === 'win32')?.name || `${game.name}.exe`;
-            // Keep the sanitized executable name based on original game to ensure it runs
-            // Allow / and \ for relative paths (e.g. win64/game.exe), strip other invalid chars
-            const sanitizedName = execName.replace(/[:*?"<>|]/g, "");
+            const { app_id, name, executable_name, icon_url } = await toQueueGame(game, nameOverride);
 
             await invoke("create_dummy_game", {
                 path: "bin",
-                executable_name: sanitizedName,
-                app_id: game.id
+                executable_name,
+                app_id
             });
 
-            const iconUrl = game.icon
-                ? `https://cdn.discordapp.com/app-icons/${game.id}/${game.icon}.png?size=64`
-                : null;
-
-            await invoke("start_game_process", {
-                name: finalName, // Use the custom name here
+            const session = await invoke<Session>("start_game_process", {
+                name, // Use the custom name here
                 path: "bin",
-                executable_name: sanitizedName,
-                app_id: game.id,
-                icon_url: iconUrl
+                executable_name,
+                app_id,
+                icon_url,
+                duration: settings.queueTimerDuration || 930
             });
 
-            setRunningGame({
-                id: game.id,
-                name: finalName,
-                executable_name: sanitizedName,
-                startTime: Date.now()
-            });
+            setRunningGame(toRunningGame(session));
 
         } catch (e) {
             console.error("Failed to start game", e);
@@ -204,6 +313,37 @@ function App() {
         }
     };
 
+    const queueCommand = async (command: string, args?: Record<string, unknown>) => {
+        try {
+            await invoke(command, args);
+        } catch (e) {
+            console.error(`${command} failed`, e);
+            alert(`Queue error: ${e}`);
+        }
+    };
+
+    const enqueueGame = async (game: Game) => {
+        let queueGame: QueueGame;
+        try {
+            queueGame = await toQueueGame(game);
+        } catch (e) {
+            console.error("Failed to resolve executable", e);
+            alert(`Queue error: ${e}`);
+            return;
+        }
+        await queueCommand("queue_enqueue", { game: queueGame });
+        // Auto-start the queue if nothing is running so it starts immediately
+        if (!runningGame && queue?.state === "idle") {
+            await queueCommand("queue_start");
+        }
+    };
+
+    const moveUp = (index: number) => {
+        const ids = queuedItems.map(item => item.id);
+        [ids[index - 1], ids[index]] = [ids[index], ids[index - 1]];
+        queueCommand("queue_reorder", { item_ids: ids });
+    };
+
 
 
     function ElapsedTime({ startTime }: { startTime: number }) {
@@ -332,6 +472,115 @@ function App() {
                                     </p>
                                 </div>
 
+                                {backendSettings && (
+                                    <div className="flex items-center justify-between space-x-2">
+                                        <div className="flex flex-col gap-1">
+                                            <span className="text-sm font-medium leading-none">
+                                                Game List Cache
+                                            </span>
+                                            <span className="text-xs text-muted-foreground">
+                                                Hours before the game list is checked for updates
+                                            </span>
+                                        </div>
+                                        <input
+                                            type="number"
+                                            min="0"
+                                            max="720"
+                                            value={Math.round(backendSettings.catalog_max_age_secs / 3600)}
+                                            onChange={(e) => updateBackendSettings({ catalog_max_age_secs: Math.max(0, parseInt(e.target.value) || 0) * 3600 })}
+                                            className="w-20 h-8 rounded-md border border-input bg-background px-2 text-sm"
+                                        />
+                                    </div>
+                                )}
+
+                                {backendSettings && (
+                                    <div className="space-y-2">
+                                        <span className="text-sm font-medium leading-none">Game List Sources</span>
+                                        {[...backendSettings.catalog_sources]
+                                            .sort((a, b) => a.priority - b.priority)
+                                            .map(source => (
+                                                <label key={source.name} className="flex items-center justify-between text-xs gap-2">
+                                                    <span className="truncate" title={source.type === "http" ? source.url : source.path}>
+                                                        {source.name} <span className="text-muted-foreground">({source.type}, {source.format})</span>
+                                                    </span>
+                                                    <input
+                                                        type="checkbox"
+                                                        checked={source.enabled}
+                                                        onChange={(e) => updateBackendSettings({
+                                                            catalog_sources: backendSettings.catalog_sources.map(s =>
+                                                                s === source ? { ...s, enabled: e.target.checked } : s)
+                                                        })}
+                                                    />
+                                                </label>
+                                            ))}
+                                        <p className="text-xs text-muted-foreground italic">
+                                            More sources can be added to settings.json in the app data folder.
+                                        </p>
+                                    </div>
+                                )}
+
+                                {backendSettings && (
+                                    <div className="space-y-2">
+                                        <span className="text-sm font-medium leading-none">Games Folder</span>
+                                        <input
+                                            type="text"
+                                            placeholder="Default"
+                                            defaultValue={backendSettings.games_root ?? ""}
+                                            onBlur={(e) => {
+                                                const value = e.target.value.trim() || null;
+                                                if (value !== backendSettings.games_root) updateBackendSettings({ games_root: value });
+                                            }}
+                                            className="w-full h-8 rounded-md border border-input bg-background px-2 text-sm"
+                                        />
+                                        {gamesRoot && (
+                                            <p className="text-xs text-muted-foreground break-all">
+                                                {gamesRoot.path}
+                                                {gamesRoot.source === "portable" && " (portable mode)"}
+                                            </p>
+                                        )}
+                                        <div className="flex items-center justify-between text-xs gap-2">
+                                            <span className="text-muted-foreground">Remove games unused for (days, 0 = never)</span>
+                                            <input
+                                                type="number"
+                                                min="0"
+                                                max="3650"
+                                                value={backendSettings.games_retention_days}
+                                                onChange={(e) => updateBackendSettings({ games_retention_days: Math.max(0, parseInt(e.target.value) || 0) })}
+                                                className="w-20 h-8 rounded-md border border-input bg-background px-2 text-sm"
+                                            />
+                                        </div>
+                                        <button onClick={loadDummyGames} className="text-xs underline text-muted-foreground hover:text-foreground">
+                                            {dummyGames ? "Refresh created games" : "Show created games"}
+                                        </button>
+                                        {dummyGames && (
+                                            <div className="max-h-48 overflow-y-auto space-y-1">
+                                                {dummyGames.length === 0 && (
+                                                    <p className="text-xs text-muted-foreground">No games created yet.</p>
+                                                )}
+                                                {dummyGames.map(game => (
+                                                    <div key={game.app_id} className="flex items-center justify-between text-xs gap-2">
+                                                        <span className="truncate" title={game.protected ?? game.path}>
+                                                            {game.name ?? game.app_id}
+                                                            <span className="text-muted-foreground">
+                                                                {" "}({(game.size / 1048576).toFixed(1)} MB
+                                                                {game.last_used && `, ${new Date(game.last_used).toLocaleDateString()}`})
+                                                            </span>
+                                                        </span>
+                                                        <button
+                                                            onClick={() => removeDummyGames([game.app_id])}
+                                                            disabled={!!game.protected}
+                                                            title={game.protected ?? "Remove"}
+                                                            className="text-muted-foreground hover:text-destructive disabled:opacity-30"
+                                                        >
+                                                            <Trash2 className="w-3 h-3" />
+                                                        </button>
+                                                    </div>
+                                                ))}
+                                            </div>
+                                        )}
+                                    </div>
+                                )}
+
                                 <div className="flex items-center justify-between space-x-2">
                                     <div className="flex flex-col gap-1">
                                         <span className="text-sm font-medium leading-none peer-disabled:cursor-not-allowed peer-disabled:opacity-70">
@@ -412,8 +661,31 @@ function App() {
                     </div>
                 )}
 
+                {/* Queue left behind by a previous run */}
+                {queue?.recovered && (
+                    <div className="bg-card/50 backdrop-blur-md border border-yellow-500/30 rounded-lg p-4 flex items-center justify-between animate-in fade-in">
+                        <p className="text-sm">
+                            Your last queue was interrupted with {queuedItems.length + (queue.current ? 1 : 0)} game(s) left. Resume where it left off?
+                        </p>
+                        <div className="flex gap-2">
+                            <button
+                                onClick={() => queueCommand("queue_resume")}
+                                className="text-xs bg-primary/20 text-primary hover:bg-primary/30 px-3 py-1 rounded-md transition-colors"
+                            >
+                                Resume
+                            </button>
+                            <button
+                                onClick={() => queueCommand("queue_clear")}
+                                className="text-xs text-muted-foreground hover:text-destructive px-2"
+                            >
+                                Discard
+                            </button>
+                        </div>
+                    </div>
+                )}
+
                 {/* Active Game Status & Queue Info */}
-                {(runningGame || queue.length > 0) && (
+                {(runningGame || queuedItems.length > 0) && (
                     <div className="space-y-4">
                         {runningGame && (
                             <div className="bg-card/50 backdrop-blur-md border border-white/10 rounded-xl p-6 shadow-2xl animate-in fade-in slide-in-from-top-4 ring-1 ring-primary/20 relative overflow-hidden">
@@ -434,58 +706,78 @@ function App() {
                                             Duration: <ElapsedTime startTime={runningGame.startTime} />
                                         </p>
                                     </div>
-                                    <button
-                                        onClick={stopGame}
-                                        className="inline-flex items-center justify-center rounded-md text-sm font-medium transition-colors focus-visible:outline-none focus-visible:ring-1 focus-visible:ring-ring disabled:pointer-events-none disabled:opacity-50 bg-destructive text-destructive-foreground shadow hover:bg-destructive/90 h-9 px-4 py-2 gap-2"
-                                    >
-                                        <Square className="h-4 w-4" /> Stop
-                                    </button>
+                                    <div className="flex gap-2">
+                                        <button
+                                            onClick={() => extendGame(300)}
+                                            title="Extend the runner's target duration by 5 minutes"
+                                            className="inline-flex items-center justify-center rounded-md text-sm font-medium transition-colors border border-input bg-transparent shadow-sm hover:bg-accent hover:text-accent-foreground h-9 px-4 py-2 gap-2"
+                                        >
+                                            <Plus className="h-4 w-4" /> 5m
+                                        </button>
+                                        <button
+                                            onClick={stopGame}
+                                            className="inline-flex items-center justify-center rounded-md text-sm font-medium transition-colors focus-visible:outline-none focus-visible:ring-1 focus-visible:ring-ring disabled:pointer-events-none disabled:opacity-50 bg-destructive text-destructive-foreground shadow hover:bg-destructive/90 h-9 px-4 py-2 gap-2"
+                                        >
+                                            <Square className="h-4 w-4" /> Stop
+                                        </button>
+                                    </div>
                                 </div>
                             </div>
                         )}
 
-                        {queue.length > 0 && (
+                        {(queuedItems.length > 0 || queue?.current) && queue && (
                             <div className="bg-card/30 backdrop-blur border border-white/5 rounded-lg p-4">
                                 <div className="flex items-center justify-between mb-2">
                                     <h4 className="text-sm font-semibold flex items-center gap-2">
-                                        Quest Queue ({queue.length})
-                                        {isQueueRunning ? (
+                                        Quest Queue ({queuedItems.length})
+                                        {queue.state === "running" ? (
                                             <span className="text-xs bg-green-500/20 text-green-400 px-2 py-0.5 rounded-full">Active</span>
                                         ) : (
                                             <span className="text-xs bg-yellow-500/20 text-yellow-400 px-2 py-0.5 rounded-full">Paused</span>
                                         )}
                                         {/* Queue Estimator */}
-                                        {queue.length > 0 && (
+                                        {queuedItems.length > 0 && (
                                             <span className="text-xs text-muted-foreground ml-2">
-                                                Est. Finish: {new Date(Date.now() + (queue.length * (settings.queueTimerDuration || 930) * 1000) + (runningGame ? ((settings.queueTimerDuration || 930) * 1000) - (Date.now() - runningGame.startTime) : 0)).toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' })}
+                                                Est. Finish: {new Date(Date.now() + (queuedItems.length * queue.item_duration * 1000) + (queue.current?.started_at ? (queue.item_duration * 1000) - (Date.now() - queue.current.started_at) : 0)).toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' })}
                                             </span>
                                         )}
                                     </h4>
                                     <div className="flex gap-2">
-                                        {!isQueueRunning && !runningGame && (
+                                        {queue.state === "idle" && !runningGame && (
                                             <button
-                                                onClick={() => {
-                                                    // Immediately trigger start mechanism via state
-                                                    if (queue.length > 0) {
-                                                        setIsQueueRunning(true);
-                                                        // Effect will pick it up
-                                                    }
-                                                }}
+                                                onClick={() => queueCommand("queue_start")}
                                                 className="text-xs bg-primary/20 text-primary hover:bg-primary/30 px-3 py-1 rounded-md transition-colors"
                                             >
                                                 Start Queue
                                             </button>
                                         )}
-                                        {isQueueRunning && (
+                                        {queue.state === "paused" && (
                                             <button
-                                                onClick={() => setIsQueueRunning(false)}
+                                                onClick={() => queueCommand("queue_resume")}
+                                                className="text-xs bg-primary/20 text-primary hover:bg-primary/30 px-3 py-1 rounded-md transition-colors"
+                                            >
+                                                Resume
+                                            </button>
+                                        )}
+                                        {queue.state === "running" && (
+                                            <button
+                                                onClick={() => queueCommand("queue_pause")}
                                                 className="text-xs bg-yellow-500/10 text-yellow-500 hover:bg-yellow-500/20 px-3 py-1 rounded-md transition-colors"
                                             >
                                                 Pause
                                             </button>
                                         )}
+                                        {queue.current && (
+                                            <button
+                                                onClick={() => queueCommand("queue_skip")}
+                                                title={`Skip ${queue.current.item.name}`}
+                                                className="text-xs text-muted-foreground hover:text-foreground px-2 inline-flex items-center gap-1"
+                                            >
+                                                <SkipForward className="w-3 h-3" /> Skip
+                                            </button>
+                                        )}
                                         <button
-                                            onClick={() => { setQueue([]); setIsQueueRunning(false); }}
+                                            onClick={() => queueCommand("queue_clear")}
                                             className="text-xs text-muted-foreground hover:text-destructive px-2"
                                         >
                                             Clear
@@ -493,10 +785,15 @@ function App() {
                                     </div>
                                 </div>
                                 <div className="space-y-1">
-                                    {queue.map((g, i) => (
-                                        <div key={i} className="flex items-center justify-between text-sm p-2 bg-black/20 rounded">
-                                            <span>{i + 1}. {g.name}</span>
-                                            <button onClick={() => setQueue(q => q.filter((_, idx) => idx !== i))} className="text-muted-foreground hover:text-white"><X className="w-3 h-3" /></button>
+                                    {queuedItems.map((item, i) => (
+                                        <div key={item.id} className="flex items-center justify-between text-sm p-2 bg-black/20 rounded">
+                                            <span>{i + 1}. {item.name}</span>
+                                            <div className="flex gap-2">
+                                                {i > 0 && (
+                                                    <button onClick={() => moveUp(i)} title="Move up" className="text-muted-foreground hover:text-white"><ChevronUp className="w-3 h-3" /></button>
+                                                )}
+                                                <button onClick={() => queueCommand("queue_remove", { item_id: item.id })} className="text-muted-foreground hover:text-white"><X className="w-3 h-3" /></button>
+                                            </div>
                                         </div>
                                     ))}
                                 </div>
@@ -528,6 +825,20 @@ function App() {
                                     autoFocus
                                 />
                             </div>
+                            {selectedDetails && selectedDetails.game.id === selectedGame.id && (
+                                <div className="space-y-1 text-xs text-muted-foreground">
+                                    <p>Listed by: {selectedDetails.provenance.sources.join(", ")}</p>
+                                    {selectedDetails.game.aliases && selectedDetails.game.aliases.length > 0 && (
+                                        <p>Also known as: {selectedDetails.game.aliases.map((alias, i) =>
+                                            `${alias} (${selectedDetails.provenance.aliases[i]})`).join(", ")}</p>
+                                    )}
+                                    {selectedDetails.game.executables?.map((exe, i) => (
+                                        <p key={`${exe.os}-${exe.name}`} className="font-mono truncate">
+                                            {exe.os}: {exe.name}{exe.is_launcher ? " (launcher)" : ""} <span className="font-sans">from {selectedDetails.provenance.executables[i]}</span>
+                                        </p>
+                                    ))}
+                                </div>
+                            )}
                             <div className="flex justify-end gap-3">
                                 <button
                                     onClick={() => setSelectedGame(null)}
@@ -556,6 +867,68 @@ function App() {
                         value={search}
                         onChange={(e) => setSearch(e.target.value)}
                     />
+                    {(catalogInfo || fetchReport || catalogProgress) && (
+                        <div className="flex items-center justify-end gap-2 mt-1 text-xs text-muted-foreground">
+                            {loading && catalogProgress ? (
+                                <span>
+                                    Loading game list: {catalogProgress.done}/{catalogProgress.total} sources, {catalogProgress.games} games
+                                </span>
+                            ) : catalogInfo ? (
+                                <span className={catalogInfo.freshness === "stale" ? "text-yellow-500" : ""}>
+                                    {catalogInfo.total} games, {catalogInfo.freshness === "fresh" && "up to date"}
+                                    {catalogInfo.freshness === "cached" && "from cache"}
+                                    {catalogInfo.freshness === "stale" && "offline: showing last saved list"}
+                                    {catalogInfo.updated_at && ` (${new Date(catalogInfo.updated_at).toLocaleString()})`}
+                                </span>
+                            ) : (
+                                <span className="text-destructive">Game list unavailable</span>
+                            )}
+                            {fetchReport && (
+                                <button
+                                    onClick={() => setShowDiagnostics(v => !v)}
+                                    className="underline hover:text-foreground"
+                                >
+                                    {showDiagnostics ? "Hide sources" : "Sources"}
+                                </button>
+                            )}
+                            <button
+                                onClick={() => loadGames(true)}
+                                disabled={loading}
+                                title="Refresh game list"
+                                className="hover:text-foreground disabled:opacity-50"
+                            >
+                                <RefreshCw className={`w-3 h-3 ${loading ? "animate-spin" : ""}`} />
+                            </button>
+                        </div>
+                    )}
+                    {showDiagnostics && fetchReport && (
+                        <div className="mt-2 rounded-md border border-white/10 bg-card/50 p-3 space-y-2 text-xs">
+                            <p className="text-muted-foreground">
+                                Last refresh {new Date(fetchReport.started_at).toLocaleString()}, {fetchReport.duration_ms}ms, {fetchReport.games} games
+                            </p>
+                            {fetchReport.sources.map(source => (
+                                <div key={source.name} className="space-y-0.5">
+                                    <p>
+                                        <span className="font-medium">{source.name}</span>{" "}
+                                        <span className={source.status === "failed" ? "text-destructive" : source.status === "stale" ? "text-yellow-500" : "text-green-500"}>
+                                            {source.status}
+                                        </span>
+                                        {source.http_status !== null && ` · HTTP ${source.http_status}`}
+                                        {` · ${source.latency_ms}ms · ${source.accepted}/${source.entries} entries`}
+                                        {source.malformed > 0 && ` · ${source.malformed} malformed`}
+                                    </p>
+                                    <p className="text-muted-foreground truncate" title={source.location}>{source.location}</p>
+                                    {source.error && <p className="text-destructive">{source.error}</p>}
+                                    {source.warnings.map((w, i) => <p key={i} className="text-yellow-500">{w}</p>)}
+                                    {source.malformed_samples.map(m => (
+                                        <p key={m.index} className="text-muted-foreground font-mono truncate">
+                                            #{m.index}{m.id ? ` (${m.id})` : ""}: {m.error}
+                                        </p>
+                                    ))}
+                                </div>
+                            ))}
+                        </div>
+                    )}
                 </div>
 
                 {/* Game List Area */}
@@ -572,13 +945,13 @@ function App() {
                         </div>
                     ) : (
                         // Results View
-                        loading ? (
+                        loading && searchHits.length === 0 && customMatches.length === 0 ? (
                             <div className="flex justify-center py-20">
                                 <Loader2 className="h-10 w-10 animate-spin text-primary" />
                             </div>
                         ) : (
                             <div className="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 gap-4">
-                                {filteredGames.slice(0, 50).map((game) => (
+                                {filteredGames.map((game) => (
                                     <div
                                         key={game.id}
                                         className="group relative flex flex-col justify-between space-y-2 rounded-lg border border-border bg-card p-4 hover:bg-accent/50 transition-colors"
@@ -618,6 +991,11 @@ function App() {
                                                     <p className="text-xs text-muted-foreground truncate">
                                                         ID: {game.id}
                                                     </p>
+                                                    {matchNotes.has(game.id) && (
+                                                        <p className="text-xs text-muted-foreground truncate" title={matchNotes.get(game.id)}>
+                                                            {matchNotes.get(game.id)}
+                                                        </p>
+                                                    )}
                                                 </div>
                                             </div>
                                         </div>
@@ -630,7 +1008,7 @@ function App() {
                                         </button>
                                         {(() => {
                                             const isRunning = runningGame?.id === game.id;
-                                            const isInQueue = queue.some(g => g.id === game.id);
+                                            const isInQueue = queuedItems.some(item => item.app_id === game.id);
 
                                             if (isRunning) {
                                                 return (
@@ -652,14 +1030,7 @@ function App() {
                                                 <button
                                                     onClick={(e) => {
                                                         e.stopPropagation();
-                                                        setQueue(q => {
-                                                            if (q.some(existing => existing.id === game.id)) return q;
-                                                            return [...q, game];
-                                                        });
-                                                        // Auto-enable queue if nothing is running so it starts immediately
-                                                        if (!runningGame && !isQueueRunning) {
-                                                            setIsQueueRunning(true);
-                                                        }
+                                                        enqueueGame(game);
                                                     }}
                                                     className="w-full inline-flex items-center justify-center rounded-md text-sm font-medium transition-colors focus-visible:outline-none focus-visible:ring-1 focus-visible:ring-ring disabled:pointer-events-none disabled:opacity-50 border border-input bg-transparent hover:bg-accent hover:text-accent-foreground h-9 px-4 py-2 mt-2 gap-2"
                                                 >
@@ -669,6 +1040,11 @@ function App() {
                                         })()}
                                     </div>
                                 ))}
+                                {searchTotal > searchHits.length && (
+                                    <div className="col-span-full text-center text-sm text-muted-foreground">
+                                        Showing the best {searchHits.length} of {searchTotal} matches.
+                                    </div>
+                                )}
                                 {filteredGames.length === 0 && (
                                     <div className="col-span-full text-center py-10 text-muted-foreground">
                                         No games found matching "{search}".
@@ -1,14 +1,118 @@
+// A detectable application as returned by `fetch_game_list`
 export interface Game {
     id: string;
     name: string;
+    aliases?: string[];
     icon?: string | null;
     splash?: string | null;
     executables?: Array<{
-        os: string;
+        os: string; // "win32" | "darwin" | "linux"
         name: string;
+        is_launcher?: boolean;
+        arguments?: string;
     }>;
 }
 
+// Which source contributed each field of a merged game
+export interface Provenance {
+    sources: string[];
+    name: string;
+    icon: string | null;
+    splash: string | null;
+    aliases: string[]; // parallel to Game.aliases
+    executables: string[]; // parallel to Game.executables
+}
+
+// Returned by `get_game_details`
+export interface GameDetails {
+    game: Game;
+    provenance: Provenance;
+}
+
+// Per-source result of the last catalog refresh (`get_fetch_report`)
+export interface SourceReport {
+    name: string;
+    location: string;
+    format: "discord" | "gist" | "native";
+    status: "fresh" | "cached" | "stale" | "failed";
+    http_status: number | null;
+    latency_ms: number;
+    entries: number;
+    accepted: number;
+    malformed: number;
+    malformed_samples: Array<{ index: number; id: string | null; error: string }>;
+    warnings: string[];
+    error: string | null;
+}
+
+// Payload of `catalog_progress`, emitted as each catalog source finishes loading
+export interface CatalogProgress {
+    source: string;
+    status: SourceReport["status"];
+    done: number;
+    total: number;
+    games: number; // in the sources finished so far
+    published: boolean; // those games can already be searched
+}
+
+// Returned by `get_catalog_page`
+export interface CatalogPage {
+    total: number;
+    offset: number;
+    games: Game[];
+}
+
+// Returned by `get_catalog_counts`
+export interface CatalogCounts {
+    games: number;
+    by_source: Record<string, number>;
+    by_os: Record<string, number>;
+}
+
+// Returned by `resolve_executable`
+export interface ResolvedExecutable {
+    app_id: string;
+    os: "win32" | "darwin" | "linux";
+    candidates: Array<{
+        name: string; // relative path, folders separated by "/"
+        os: string | null; // null when named after the game
+        score: number;
+        reasons: string[];
+    }>;
+    skipped: Array<{ os: string; name: string; reason: string }>;
+}
+
+// Returned by `search_games`
+export interface SearchHit {
+    game: Game;
+    score: number;
+    matched_field: "name" | "alias" | "executable";
+    matched_text: string;
+}
+
+export interface SearchPage {
+    query: string;
+    total: number; // matches across all pages
+    offset: number;
+    hits: SearchHit[];
+}
+
+export interface FetchReport {
+    started_at: number; // Unix time in ms
+    duration_ms: number;
+    games: number;
+    sources: SourceReport[];
+}
+
+export type CatalogFreshness = "fresh" | "cached" | "stale";
+
+// Returned by `fetch_game_list`; the games are read with `get_catalog_page` and `search_games`
+export interface Catalog {
+    total: number;
+    freshness: CatalogFreshness;
+    updated_at: number | null; // Unix time in ms of the oldest data
+}
+
 export interface RunningGame {
     id: string;
     name: string;
@@ -21,3 +125,139 @@ export interface Settings {
     notificationsEnabled: boolean;
     queueTimerDuration: number; // in seconds
 }
+
+// A place the game list is read from; lower priority goes first
+export type CatalogSourceConfig = {
+    name: string;
+    format: "discord" | "gist" | "native";
+    priority: number;
+    enabled: boolean;
+} & (
+    | { type: "http"; url: string }
+    | { type: "file"; path: string }
+    | { type: "directory"; path: string }
+);
+
+// Settings kept by the backend (`get_settings` / `update_settings`)
+export interface BackendSettings {
+    catalog_max_age_secs: number;
+    catalog_sources: CatalogSourceConfig[];
+    http: HttpSettings;
+    games_root: string | null; // relative paths are taken from the data folder
+    games_retention_days: number; // 0 keeps unused game folders forever
+}
+
+// Returned by `list_dummy_games`
+export interface DummyGame {
+    app_id: string;
+    name: string | null;
+    path: string;
+    size: number; // bytes removing it would free
+    shared: number; // bytes of runner copies linked elsewhere, kept on removal
+    last_used: number | null; // Unix time in ms
+    protected: string | null; // why it can't be removed
+}
+
+// Returned by `remove_dummy_games`, one per app id
+export interface DummyGameRemoval {
+    app_id: string;
+    freed: number | null; // bytes, null if kept
+    error: string | null;
+}
+
+// Returned by `get_games_root`
+export interface GamesRoot {
+    path: string;
+    source: "settings" | "portable" | "data_dir";
+}
+
+export interface HttpSettings {
+    connect_timeout_secs: number;
+    read_timeout_secs: number;
+    fetch_deadline_secs: number;
+    max_retries: number;
+    proxy: string | null;
+    ca_certificate: string | null;
+    user_agent: string;
+}
+
+// Events the runner reports with `--status`, forwarded by the backend as `game_status`
+export type RunnerEvent =
+    | { event: "started"; pid: number; title: string; duration: number }
+    | { event: "icon_loaded"; url: string }
+    | { event: "icon_failed"; url: string; error: string }
+    | { event: "heartbeat"; elapsed: number; duration: number }
+    | { event: "completed"; elapsed: number }
+    | { event: "exiting"; reason: string; code: number };
+
+export interface GameStatusPayload {
+    app_id: string;
+    pid: number;
+    ts: number; // Unix time in ms
+    status: RunnerEvent;
+}
+
+// Typed error returned by `stop_process`
+export interface ProcessError {
+    kind: "not_running" | "not_found" | "permission_denied" | "timeout" | "os";
+    detail: unknown;
+}
+
+export type SessionStatus = "running" | "stopping" | "stopped" | "exited";
+
+// A runner launched by the backend (`list_sessions` / `get_session`)
+export interface Session {
+    id: number;
+    app_id: string;
+    name: string;
+    pid: number;
+    exe_path: string;
+    started_at: number; // Unix time in ms
+    status: SessionStatus;
+    elapsed: number;
+    duration: number | null;
+    completed: boolean;
+    exit_code: number | null;
+}
+
+// Payload of `session_changed` and `game_exited`
+export interface SessionEvent {
+    session: Session;
+    reason: "started" | "progress" | "completed" | "stopping" | "stopped" | "exited";
+}
+
+// Game handed to `queue_enqueue`
+export interface QueueGame {
+    app_id: string;
+    name: string;
+    executable_name: string;
+    icon_url?: string | null;
+}
+
+export interface QueueItem extends QueueGame {
+    id: number;
+    elapsed: number; // seconds played before an interrupted run
+}
+
+export type QueueState = "idle" | "running" | "paused";
+
+// Payload of `queue_changed`, also returned by `get_queue`
+export interface QueueSnapshot {
+    state: QueueState;
+    item_duration: number; // in seconds
+    current: {
+        item: QueueItem;
+        session_id: number | null;
+        started_at: number | null; // Unix time in ms
+    } | null;
+    items: QueueItem[];
+    recovered: boolean; // restored after a restart, waiting to be resumed
+}
+
+// Payload of `queue_item_completed`
+export interface QueueItemCompleted {
+    item: QueueItem;
+    session_id: number | null;
+    reason: "completed" | "skipped" | "stopped" | "exited" | "failed";
+    error: string | null;
+}